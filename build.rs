@@ -3,35 +3,108 @@ use std::fs::File;
 use std::io::{self, BufRead};
 use std::path::Path;
 
-fn main() -> io::Result<()> {
-    println!("cargo::rerun-if-env-changed=WORLD_SRC");
-    let path = env::var("WORLD_SRC").unwrap_or_else(|_| "./data/world_10.txt".to_string());
-    let input_file_path = Path::new(path.as_str());
-    let file = File::open(&input_file_path).expect(format!("File not found").as_str());
-    let reader = io::BufReader::new(file);
-    let lines: Vec<String> = reader
-        .lines()
-        .filter(|l| !l.as_ref().unwrap().is_empty())
-        .collect::<Result<_, _>>()
-        .unwrap();
+/// Baked datasets: the constant suffix, the env var overriding the source and the
+/// default source
+const DATASETS: [(&str, &str, &str); 3] = [
+    ("LOW", "WORLD_SRC_LOW", "./data/world_110.txt"),
+    ("MED", "WORLD_SRC_MED", "./data/world_50.txt"),
+    ("HIGH", "WORLD_SRC_HIGH", "./data/world_10.txt"),
+];
 
-    let out_dir = env::var("OUT_DIR").unwrap();
-    let dest_path = Path::new(&out_dir).join("coordinates.rs");
+fn main() -> io::Result<()> {
+    println!("cargo::rerun-if-env-changed=WORLD_STRIDE");
+    // keep every Nth coordinate for a lighter binary
+    let stride = match env::var("WORLD_STRIDE") {
+        Ok(stride) => stride
+            .parse::<usize>()
+            .ok()
+            .filter(|stride| *stride > 0)
+            .unwrap_or_else(|| panic!("invalid WORLD_STRIDE `{stride}`")),
+        Err(_) => 1,
+    };
 
-    let mut output = String::from(format!(
-        "pub const COORDINATES: [(f64, f64); {}] = [\n",
-        lines.len()
-    ));
-    for line in lines {
-        let parts: Vec<&str> = line.split_whitespace().collect();
-        let (lat, lon) = (
-            parts[0].parse::<f64>().unwrap(),
-            parts[1].parse::<f64>().unwrap(),
+    let mut output = String::new();
+    for (suffix, var, default) in DATASETS {
+        println!("cargo::rerun-if-env-changed={var}");
+        let path = env::var(var).unwrap_or_else(|_| default.to_string());
+        let (coordinates, breaks) = read_coordinates(&path, stride);
+        if stride > 1 {
+            println!(
+                "cargo::warning=WORLD_STRIDE={stride} keeps {} {suffix} world points",
+                coordinates.len()
+            );
+        }
+        // reported by `plou --version`
+        println!("cargo::rustc-env=PLOU_WORLD_SRC_{suffix}={path}");
+        println!(
+            "cargo::rustc-env=PLOU_WORLD_POINTS_{suffix}={}",
+            coordinates.len()
         );
-        output.push_str(&format!("    ({:.10}, {:.10}),\n", lat, lon))
+
+        output.push_str(&format!(
+            "pub const COORDINATES_{suffix}: [(f64, f64); {}] = [\n",
+            coordinates.len()
+        ));
+        // the canvas takes (x, y), that is (lon, lat)
+        for (lat, lon) in coordinates {
+            output.push_str(&format!("    ({:.10}, {:.10}),\n", lon, lat))
+        }
+        output.push_str("];\n");
+
+        output.push_str(&format!(
+            "pub const PATH_BREAKS_{suffix}: [usize; {}] = {breaks:?};\n",
+            breaks.len()
+        ));
     }
 
-    output.push_str("];\n");
+    let out_dir = env::var("OUT_DIR").unwrap();
+    let dest_path = Path::new(&out_dir).join("coordinates.rs");
     std::fs::write(&dest_path, output)?;
     Ok(())
 }
+
+/// Every `stride`th coordinate of the file at `path`, and the indices where a new
+/// path starts, marked by blank lines in the file
+fn read_coordinates(path: &str, stride: usize) -> (Vec<(f64, f64)>, Vec<usize>) {
+    let file = File::open(path).unwrap_or_else(|_| panic!("File not found: {path}"));
+    let reader = io::BufReader::new(file);
+    let mut coordinates = Vec::new();
+    let mut breaks = Vec::new();
+    let mut new_path = false;
+    let mut first_line = true;
+    let mut read = 0;
+    for line in reader.lines() {
+        let line = line.unwrap();
+        if line.trim().is_empty() {
+            new_path = true;
+            continue;
+        }
+        let first = std::mem::replace(&mut first_line, false);
+        let Some(coordinate) = parse_line(&line) else {
+            // skip a header such as `lat,lon`, detected by the first line not being numbers
+            if first {
+                continue;
+            }
+            panic!("invalid coordinates `{line}`");
+        };
+        if read % stride == 0 {
+            if new_path && !coordinates.is_empty() {
+                breaks.push(coordinates.len());
+            }
+            new_path = false;
+            coordinates.push(coordinate);
+        }
+        read += 1;
+    }
+    (coordinates, breaks)
+}
+
+/// Reads the first two numbers of a line separated by whitespace or commas
+fn parse_line(line: &str) -> Option<(f64, f64)> {
+    let mut parts = line
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|part| !part.is_empty());
+    let lat = parts.next()?.parse::<f64>().ok()?;
+    let lon = parts.next()?.parse::<f64>().ok()?;
+    Some((lat, lon))
+}
@@ -0,0 +1,549 @@
+use std::path::PathBuf;
+
+use crate::geo::{CoordinateFormat, Projection};
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use color_eyre::{
+    eyre::{bail, eyre, WrapErr},
+    Result,
+};
+
+#[derive(Debug, Default)]
+pub struct Args {
+    /// horizontal and vertical multipliers applied to mouse drag panning
+    pub drag_sensitivity: Option<(f64, f64)>,
+    /// WKT file with lines and polygons to draw over the map
+    pub wkt: Option<PathBuf>,
+    /// GeoJSON file with points, lines and polygons to draw over the map
+    pub geojson: Option<PathBuf>,
+    /// `lat lon` file drawn instead of the baked world coastline
+    pub data: Option<PathBuf>,
+    /// start with the debug overlay showing
+    pub debug: bool,
+    /// what a middle click resets the view to
+    pub reset_target: Option<ResetTarget>,
+    /// keep drawing overlays during a mouse drag instead of hiding them
+    pub keep_overlays_while_dragging: bool,
+    /// (lon, lat) of a fixed reference marker
+    pub home: Option<(f64, f64)>,
+    /// layers that are not drawn while the view is being moved
+    pub heavy_layers: Vec<Layer>,
+    /// words of the command run to open the view elsewhere, with `{lon}`, `{lat}`
+    /// and `{zoom}` replaced by the current center and web map zoom level
+    pub open_command: Option<Vec<String>>,
+    /// pan by whole cells while dragging, carrying the fractional remainder
+    pub snap_drag: bool,
+    /// most grid points the coastline lights per terminal cell, all of them when
+    /// missing or 0
+    pub max_points_per_cell: Option<u8>,
+    pub coordinate_format: Option<CoordinateFormat>,
+    /// coastline thickness in grid points, 1 is the normal hairline
+    pub thickness: Option<u8>,
+    /// TOML file listing the layers to draw
+    pub manifest: Option<PathBuf>,
+    /// print the version and the baked world dataset, then exit
+    pub version: bool,
+    /// key that quits, `Some(None)` disables it leaving only Ctrl+C
+    pub quit_key: Option<Option<KeyBinding>>,
+    /// most redraws per second
+    pub max_fps: Option<u32>,
+    /// decimals of the displayed coordinates
+    pub precision: Option<usize>,
+    /// degrees the simplified coastline may stray from the full one
+    pub simplify_tolerance: Option<f64>,
+    /// longitude displayed as 0
+    pub prime_meridian: Option<f64>,
+    /// labelled markers shown from launch
+    pub marks: Vec<Mark>,
+    /// CSV files with more markers, as `lon,lat,label` rows
+    pub mark_files: Vec<PathBuf>,
+    /// blink the markers to draw attention to them
+    pub blink_marks: bool,
+    /// make every animation instant or steady
+    pub reduce_motion: bool,
+    pub projection: Option<Projection>,
+    /// widest graticule spacing in degrees, used when zoomed out
+    pub graticule_step: Option<f64>,
+    /// [min_lon, min_lat, max_lon, max_lat] of the view at launch
+    pub bbox: Option<[f64; 4]>,
+}
+
+/// A point of interest given as `lat,lon` with an optional `:label`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mark {
+    pub lon: f64,
+    pub lat: f64,
+    pub label: Option<String>,
+}
+
+impl std::str::FromStr for Mark {
+    type Err = color_eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (position, label) = match s.split_once(':') {
+            Some((position, label)) => (position, Some(label.to_string())),
+            None => (s, None),
+        };
+        let (lat, lon) = parse_pair(position)?;
+        Ok(Self { lon, lat, label })
+    }
+}
+
+/// Layers of the map that can be configured independently
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum Layer {
+    /// the baked world coastline
+    Coastline,
+    /// lines loaded from the user's files
+    Lines,
+    /// the captured snapshot drawn beneath the map
+    Snapshot,
+}
+
+impl std::str::FromStr for Layer {
+    type Err = color_eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "coastline" => Ok(Self::Coastline),
+            "lines" => Ok(Self::Lines),
+            "snapshot" => Ok(Self::Snapshot),
+            other => bail!("unknown layer `{other}`, expected coastline, lines or snapshot"),
+        }
+    }
+}
+
+/// A key with optional modifiers, written as `q`, `ctrl+q` or `alt+f4`
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct KeyBinding {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl Default for KeyBinding {
+    fn default() -> Self {
+        Self {
+            code: KeyCode::Char('q'),
+            modifiers: KeyModifiers::NONE,
+        }
+    }
+}
+
+impl KeyBinding {
+    pub fn matches(&self, event: &KeyEvent) -> bool {
+        // shift is already part of the character, `Q` rather than `shift+q`
+        let modifiers = match event.code {
+            KeyCode::Char(_) => event.modifiers.difference(KeyModifiers::SHIFT),
+            _ => event.modifiers,
+        };
+        event.code == self.code && modifiers == self.modifiers
+    }
+}
+
+impl std::str::FromStr for KeyBinding {
+    type Err = color_eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (modifier_names, key) = s.rsplit_once('+').unwrap_or(("", s));
+        let mut modifiers = KeyModifiers::NONE;
+        for modifier in modifier_names.split('+').filter(|m| !m.is_empty()) {
+            modifiers |= match modifier.to_ascii_lowercase().as_str() {
+                "ctrl" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                other => bail!("unknown modifier `{other}`, expected ctrl or alt"),
+            };
+        }
+        let mut chars = key.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match key.to_ascii_lowercase().as_str() {
+                "esc" => KeyCode::Esc,
+                "enter" => KeyCode::Enter,
+                "tab" => KeyCode::Tab,
+                f => match f.strip_prefix('f').and_then(|n| n.parse().ok()) {
+                    Some(n) => KeyCode::F(n),
+                    None => bail!("unknown key `{key}`"),
+                },
+            },
+        };
+        Ok(Self { code, modifiers })
+    }
+}
+
+/// Written the way `--quit-key` takes it, `Ctrl+q` or `F10`
+impl std::fmt::Display for KeyBinding {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "Ctrl+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "Alt+")?;
+        }
+        match self.code {
+            KeyCode::Char(c) => write!(f, "{c}"),
+            KeyCode::F(n) => write!(f, "F{n}"),
+            KeyCode::Esc => write!(f, "Esc"),
+            KeyCode::Enter => write!(f, "Enter"),
+            KeyCode::Tab => write!(f, "Tab"),
+            code => write!(f, "{code:?}"),
+        }
+    }
+}
+
+/// Extent the viewport goes back to when reset
+#[derive(Debug, Clone, Default, Copy, Eq, PartialEq, Hash)]
+pub enum ResetTarget {
+    /// the whole world
+    #[default]
+    World,
+    /// the bounds of the loaded data, or the world if nothing is loaded
+    Data,
+}
+
+impl Args {
+    pub fn parse() -> Result<Self> {
+        Self::parse_from(std::env::args().skip(1))
+    }
+
+    fn parse_from(args: impl IntoIterator<Item = String>) -> Result<Self> {
+        let mut parsed = Self::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--drag-sensitivity" => {
+                    parsed.drag_sensitivity = Some(parse_sensitivity(&value(&mut args, &arg)?)?)
+                }
+                "--wkt" => parsed.wkt = Some(value(&mut args, &arg)?.into()),
+                "--geojson" => parsed.geojson = Some(value(&mut args, &arg)?.into()),
+                "--data" => parsed.data = Some(value(&mut args, &arg)?.into()),
+                "--debug" => parsed.debug = true,
+                "--reset-to" => {
+                    parsed.reset_target = Some(match value(&mut args, &arg)?.as_str() {
+                        "world" => ResetTarget::World,
+                        "data" => ResetTarget::Data,
+                        other => bail!("unknown reset target `{other}`, expected world or data"),
+                    })
+                }
+                "--keep-overlays-while-dragging" => parsed.keep_overlays_while_dragging = true,
+                "--home" => parsed.home = Some(parse_position(&value(&mut args, &arg)?)?),
+                "--heavy-layers" => {
+                    parsed.heavy_layers = value(&mut args, &arg)?
+                        .split(',')
+                        .map(|layer| layer.trim().parse())
+                        .collect::<Result<_>>()?
+                }
+                "--open-command" => {
+                    parsed.open_command = Some(split_command(&value(&mut args, &arg)?)?)
+                }
+                "--snap-drag" => parsed.snap_drag = true,
+                "--max-points-per-cell" => {
+                    let value = value(&mut args, &arg)?;
+                    parsed.max_points_per_cell = Some(
+                        value
+                            .parse()
+                            .wrap_err_with(|| format!("invalid point count `{value}`"))?,
+                    )
+                }
+                "--coordinate-format" => {
+                    parsed.coordinate_format = Some(
+                        value(&mut args, &arg)?
+                            .parse()
+                            .map_err(|err| eyre!("{err}"))?,
+                    )
+                }
+                "--thickness" => {
+                    let value = value(&mut args, &arg)?;
+                    parsed.thickness = Some(
+                        value
+                            .parse()
+                            .ok()
+                            .filter(|thickness| *thickness > 0)
+                            .ok_or_else(|| eyre!("invalid thickness `{value}`"))?,
+                    )
+                }
+                "--manifest" => parsed.manifest = Some(value(&mut args, &arg)?.into()),
+                "--version" | "-V" => parsed.version = true,
+                "--quit-key" => {
+                    parsed.quit_key = Some(match value(&mut args, &arg)?.as_str() {
+                        "none" => None,
+                        key => Some(key.parse()?),
+                    })
+                }
+                "--max-fps" => {
+                    let value = value(&mut args, &arg)?;
+                    parsed.max_fps = Some(
+                        value
+                            .parse()
+                            .ok()
+                            .filter(|fps| *fps > 0)
+                            .ok_or_else(|| eyre!("invalid frame rate `{value}`"))?,
+                    )
+                }
+                "--precision" => {
+                    let value = value(&mut args, &arg)?;
+                    parsed.precision = Some(
+                        value
+                            .parse()
+                            .wrap_err_with(|| format!("invalid precision `{value}`"))?,
+                    )
+                }
+                "--simplify-tolerance" => {
+                    let value = value(&mut args, &arg)?;
+                    parsed.simplify_tolerance = Some(
+                        value
+                            .parse()
+                            .ok()
+                            .filter(|tolerance: &f64| *tolerance >= 0.)
+                            .ok_or_else(|| eyre!("invalid tolerance `{value}`"))?,
+                    )
+                }
+                "--prime-meridian" => {
+                    let value = value(&mut args, &arg)?;
+                    parsed.prime_meridian = Some(match value.as_str() {
+                        "greenwich" => 0.,
+                        "paris" => 2.337_229,
+                        "ferro" => -17.666_667,
+                        degrees => degrees
+                            .parse()
+                            .ok()
+                            .filter(|lon: &f64| (-180.0..=180.).contains(lon))
+                            .ok_or_else(|| eyre!("invalid prime meridian `{value}`"))?,
+                    })
+                }
+                "--mark" => parsed.marks.push(value(&mut args, &arg)?.parse()?),
+                "--markers" => parsed.mark_files.push(value(&mut args, &arg)?.into()),
+                "--blink-marks" => parsed.blink_marks = true,
+                "--reduce-motion" => parsed.reduce_motion = true,
+                "--graticule-step" => {
+                    let value = value(&mut args, &arg)?;
+                    parsed.graticule_step = Some(
+                        value
+                            .parse()
+                            .ok()
+                            .filter(|step: &f64| *step > 0. && *step <= 90.)
+                            .ok_or_else(|| eyre!("invalid graticule step `{value}`"))?,
+                    )
+                }
+                "--projection" => {
+                    parsed.projection = Some(
+                        value(&mut args, &arg)?
+                            .parse()
+                            .map_err(|err| eyre!("{err}"))?,
+                    )
+                }
+                "--bbox" => parsed.bbox = Some(parse_bbox(&value(&mut args, &arg)?)?),
+                other => bail!("unknown argument `{other}`"),
+            }
+        }
+        Ok(parsed)
+    }
+}
+
+fn value(args: &mut impl Iterator<Item = String>, flag: &str) -> Result<String> {
+    args.next()
+        .ok_or_else(|| eyre!("missing value for `{flag}`"))
+}
+
+/// Splits a command into words at whitespace, keeping what is quoted with `'` or
+/// `"` in a single word
+fn split_command(command: &str) -> Result<Vec<String>> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut quote = None;
+    for c in command.chars() {
+        match quote {
+            Some(open) if c == open => quote = None,
+            Some(_) => word.get_or_insert_with(String::new).push(c),
+            None if c == '\'' || c == '"' => {
+                quote = Some(c);
+                // `""` is an empty word rather than none
+                word.get_or_insert_with(String::new);
+            }
+            None if c.is_whitespace() => words.extend(word.take()),
+            None => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    if quote.is_some() {
+        bail!("unterminated quote in `{command}`");
+    }
+    words.extend(word);
+    if words.is_empty() {
+        bail!("empty command");
+    }
+    Ok(words)
+}
+
+/// Parses a `a,b` pair of numbers
+fn parse_pair(value: &str) -> Result<(f64, f64)> {
+    let (a, b) = value
+        .split_once(',')
+        .ok_or_else(|| eyre!("expected two comma separated numbers, got `{value}`"))?;
+    let parse = |s: &str| {
+        s.trim()
+            .parse::<f64>()
+            .wrap_err_with(|| format!("invalid number `{s}` in `{value}`"))
+    };
+    Ok((parse(a)?, parse(b)?))
+}
+
+/// Parses `horizontal,vertical` drag multipliers, both finite and above zero
+fn parse_sensitivity(value: &str) -> Result<(f64, f64)> {
+    let (horizontal, vertical) = parse_pair(value)?;
+    if ![horizontal, vertical]
+        .iter()
+        .all(|factor| factor.is_finite() && *factor > 0.)
+    {
+        bail!("drag sensitivities in `{value}` must be positive numbers");
+    }
+    Ok((horizontal, vertical))
+}
+
+/// Parses a `lon,lat` position within the world
+fn parse_position(value: &str) -> Result<(f64, f64)> {
+    let (lon, lat) = parse_pair(value)?;
+    if !(-180.0..=180.).contains(&lon) {
+        bail!("the longitude in `{value}` must be within -180 and 180");
+    }
+    if !(-90.0..=90.).contains(&lat) {
+        bail!("the latitude in `{value}` must be within -90 and 90");
+    }
+    Ok((lon, lat))
+}
+
+/// Parses `min_lon,min_lat,max_lon,max_lat` bounds within the world
+fn parse_bbox(value: &str) -> Result<[f64; 4]> {
+    let bounds: Vec<f64> = value
+        .split(',')
+        .map(|s| {
+            s.trim()
+                .parse::<f64>()
+                .wrap_err_with(|| format!("invalid number `{s}` in `{value}`"))
+        })
+        .collect::<Result<_>>()?;
+    let Ok([min_x, min_y, max_x, max_y]) = <[f64; 4]>::try_from(bounds) else {
+        bail!("expected `min_lon,min_lat,max_lon,max_lat`, got `{value}`");
+    };
+    if !(-180.0..=180.).contains(&min_x) || !(-180.0..=180.).contains(&max_x) {
+        bail!("longitudes in `{value}` must be within -180 and 180");
+    }
+    if !(-90.0..=90.).contains(&min_y) || !(-90.0..=90.).contains(&max_y) {
+        bail!("latitudes in `{value}` must be within -90 and 90");
+    }
+    if min_x >= max_x || min_y >= max_y {
+        bail!("the minimums in `{value}` must be below the maximums");
+    }
+    Ok([min_x, min_y, max_x, max_y])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(values: &[&str]) -> Result<Args> {
+        Args::parse_from(values.iter().map(|s| s.to_string()))
+    }
+
+    #[test]
+    fn drag_sensitivity() {
+        let parsed = args(&["--drag-sensitivity", "1.5,0.5"]).unwrap();
+        assert_eq!(parsed.drag_sensitivity, Some((1.5, 0.5)));
+    }
+
+    #[test]
+    fn wkt_path() {
+        let parsed = args(&["--wkt", "roads.wkt"]).unwrap();
+        assert_eq!(parsed.wkt, Some(PathBuf::from("roads.wkt")));
+    }
+
+    #[test]
+    fn open_command() {
+        let parsed = args(&[
+            "--open-command",
+            "open 'https://example.com/#{zoom}/{lat}' -a \"My Browser\"",
+        ])
+        .unwrap();
+        assert_eq!(
+            parsed.open_command.unwrap(),
+            [
+                "open",
+                "https://example.com/#{zoom}/{lat}",
+                "-a",
+                "My Browser"
+            ]
+        );
+        assert!(args(&["--open-command", "open 'https://example.com"]).is_err());
+        assert!(args(&["--open-command", "  "]).is_err());
+    }
+
+    #[test]
+    fn heavy_layers() {
+        let parsed = args(&["--heavy-layers", "coastline, lines"]).unwrap();
+        assert_eq!(parsed.heavy_layers, vec![Layer::Coastline, Layer::Lines]);
+        assert!(args(&["--heavy-layers", "oceans"]).is_err());
+    }
+
+    #[test]
+    fn quit_key() {
+        let parsed = args(&["--quit-key", "ctrl+q"]).unwrap();
+        let key = parsed.quit_key.flatten().unwrap();
+        assert!(key.matches(&KeyEvent::new(KeyCode::Char('q'), KeyModifiers::CONTROL)));
+        assert!(!key.matches(&KeyEvent::from(KeyCode::Char('q'))));
+        assert_eq!(key.to_string(), "Ctrl+q");
+        assert_eq!("f10".parse::<KeyBinding>().unwrap().to_string(), "F10");
+
+        assert_eq!(args(&["--quit-key", "none"]).unwrap().quit_key, Some(None));
+        assert!(args(&["--quit-key", "hyper+q"]).is_err());
+    }
+
+    #[test]
+    fn marks() {
+        let parsed = args(&["--mark", "48.85,2.35:Paris", "--mark", "-33.9,151.2"]).unwrap();
+        assert_eq!(
+            parsed.marks,
+            vec![
+                Mark {
+                    lon: 2.35,
+                    lat: 48.85,
+                    label: Some("Paris".to_string())
+                },
+                Mark {
+                    lon: 151.2,
+                    lat: -33.9,
+                    label: None
+                },
+            ]
+        );
+        assert!(args(&["--mark", "paris"]).is_err());
+    }
+
+    #[test]
+    fn bbox() {
+        let parsed = args(&["--bbox", "-10, 35.5,5,44"]).unwrap();
+        assert_eq!(parsed.bbox, Some([-10., 35.5, 5., 44.]));
+        assert!(args(&["--bbox", "-10,35,5"]).is_err());
+        assert!(args(&["--bbox", "5,35,-10,44"]).is_err());
+        assert!(args(&["--bbox", "-10,35,5,35"]).is_err());
+        assert!(args(&["--bbox", "-10,35,190,44"]).is_err());
+        assert!(args(&["--bbox", "-10,-95,5,44"]).is_err());
+        assert!(args(&["--bbox", "-10,NaN,5,44"]).is_err());
+    }
+
+    #[test]
+    fn rejects_bad_input() {
+        assert!(args(&["--drag-sensitivity"]).is_err());
+        assert!(args(&["--drag-sensitivity", "1.5"]).is_err());
+        assert!(args(&["--drag-sensitivity", "a,b"]).is_err());
+        for sensitivity in ["NaN,1", "1,inf", "0,1", "1,-2"] {
+            assert!(
+                args(&["--drag-sensitivity", sensitivity]).is_err(),
+                "{sensitivity}"
+            );
+        }
+        assert!(args(&["--home", "190,0"]).is_err());
+        assert!(args(&["--home", "0,-91"]).is_err());
+        assert!(args(&["--home", "NaN,0"]).is_err());
+        assert!(args(&["--nope"]).is_err());
+        assert!(args(&["--graticule-step", "0"]).is_err());
+        assert!(args(&["--graticule-step", "ten"]).is_err());
+    }
+}
@@ -0,0 +1,611 @@
+use std::{
+    f64::consts::{FRAC_PI_2, FRAC_PI_4},
+    fmt,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::map::Polyline;
+
+/// Mean radius of the Earth in kilometres
+pub const EARTH_RADIUS_KM: f64 = 6371.0088;
+/// Length of a degree of latitude in kilometres
+pub const KM_PER_DEGREE: f64 = EARTH_RADIUS_KM * std::f64::consts::PI / 180.;
+
+/// Formats used to display a coordinate in the status readout
+#[derive(Debug, Clone, Default, Copy, Eq, PartialEq, Hash)]
+pub enum CoordinateFormat {
+    #[default]
+    Decimal,
+    /// decimal lon/lat followed by the Maidenhead grid locator
+    Maidenhead,
+    /// decimal lon/lat followed by the UTM zone, easting and northing
+    Utm,
+}
+
+impl std::str::FromStr for CoordinateFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "decimal" => Ok(Self::Decimal),
+            "maidenhead" => Ok(Self::Maidenhead),
+            "utm" => Ok(Self::Utm),
+            other => Err(format!(
+                "unknown coordinate format `{other}`, expected decimal, maidenhead or utm"
+            )),
+        }
+    }
+}
+
+impl CoordinateFormat {
+    pub fn next(self) -> Self {
+        match self {
+            Self::Decimal => Self::Maidenhead,
+            Self::Maidenhead => Self::Utm,
+            Self::Utm => Self::Decimal,
+        }
+    }
+
+    /// Formats a coordinate with `precision` decimals for the decimal degrees, and
+    /// the longitude counted from `prime_meridian`. Grid systems stay absolute
+    pub fn format(self, lon: f64, lat: f64, precision: usize, prime_meridian: f64) -> String {
+        let relative_lon = relative_longitude(lon, prime_meridian);
+        let decimal = format!("lon: {relative_lon:.precision$}  lat: {lat:.precision$}");
+        match self {
+            Self::Decimal => decimal,
+            Self::Maidenhead => format!("{decimal}  {}", maidenhead(lon, lat)),
+            Self::Utm => match utm(lon, lat) {
+                Some(utm) => format!("{decimal}  {utm}"),
+                None => format!("{decimal}  UTM n/a"),
+            },
+        }
+    }
+}
+
+/// Radius of the orthographic globe in canvas units, so a degree at its center spans
+/// about one unit like on the flat projections
+const GLOBE_RADIUS: f64 = 180. / std::f64::consts::PI;
+
+/// How coordinates are laid out on the canvas. The flat projections only differ in
+/// how latitudes are spaced vertically, longitudes always map linearly
+#[derive(Debug, Clone, Default, Copy, Eq, PartialEq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Projection {
+    /// latitude used as is
+    #[default]
+    Equirectangular,
+    /// conformal like slippy maps, stretching towards the poles
+    Mercator,
+    /// the globe seen from space above the center of the view, the far side hidden.
+    /// Panning and zooming still move the view in degrees around that center
+    Orthographic,
+}
+
+impl std::str::FromStr for Projection {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "equirectangular" => Ok(Self::Equirectangular),
+            "mercator" => Ok(Self::Mercator),
+            "orthographic" => Ok(Self::Orthographic),
+            other => Err(format!(
+                "unknown projection `{other}`, expected equirectangular, mercator or orthographic"
+            )),
+        }
+    }
+}
+
+impl Projection {
+    pub fn next(self) -> Self {
+        match self {
+            Self::Equirectangular => Self::Mercator,
+            Self::Mercator => Self::Orthographic,
+            Self::Orthographic => Self::Equirectangular,
+        }
+    }
+
+    /// Highest latitude drawn, Mercator runs off to infinity at the poles
+    pub fn max_latitude(self) -> f64 {
+        match self {
+            Self::Equirectangular | Self::Orthographic => 90.,
+            Self::Mercator => 85.,
+        }
+    }
+
+    /// Vertical canvas coordinate of `lat`, in units matching degrees at the equator.
+    /// Latitudes stay linear for the orthographic view, which is laid out in degrees
+    pub fn y(self, lat: f64) -> f64 {
+        match self {
+            Self::Equirectangular | Self::Orthographic => lat,
+            Self::Mercator => (FRAC_PI_4 + lat.to_radians() / 2.).tan().ln().to_degrees(),
+        }
+    }
+
+    /// Latitude at the vertical canvas coordinate `y`, the inverse of `y`
+    pub fn latitude(self, y: f64) -> f64 {
+        match self {
+            Self::Equirectangular | Self::Orthographic => y,
+            Self::Mercator => (2. * y.to_radians().exp().atan() - FRAC_PI_2).to_degrees(),
+        }
+    }
+
+    /// Canvas coordinates of (`lon`, `lat`) in a view centered on `center`, `None`
+    /// beyond `max_latitude` or on the far side of the globe. The globe is centered
+    /// on the canvas origin
+    pub fn point(self, lon: f64, lat: f64, (lon0, lat0): (f64, f64)) -> Option<(f64, f64)> {
+        if lat.abs() > self.max_latitude() {
+            return None;
+        }
+        match self {
+            Self::Equirectangular | Self::Mercator => Some((lon, self.y(lat))),
+            Self::Orthographic => {
+                let (lat, lat0) = (lat.to_radians(), lat0.to_radians());
+                let lon = (lon - lon0).to_radians();
+                let facing = lat0.sin() * lat.sin() + lat0.cos() * lat.cos() * lon.cos();
+                (facing >= 0.).then(|| {
+                    (
+                        GLOBE_RADIUS * lat.cos() * lon.sin(),
+                        GLOBE_RADIUS
+                            * (lat0.cos() * lat.sin() - lat0.sin() * lat.cos() * lon.cos()),
+                    )
+                })
+            }
+        }
+    }
+
+    /// Coordinate at the canvas point (`x`, `y`), the inverse of `point`. `None` off
+    /// the globe
+    pub fn coordinate(self, x: f64, y: f64, (lon0, lat0): (f64, f64)) -> Option<(f64, f64)> {
+        match self {
+            Self::Equirectangular | Self::Mercator => Some((x, self.latitude(y))),
+            Self::Orthographic => {
+                let rho = x.hypot(y);
+                if rho > GLOBE_RADIUS {
+                    return None;
+                }
+                if rho == 0. {
+                    return Some((lon0, lat0));
+                }
+                let c = (rho / GLOBE_RADIUS).asin();
+                let lat0 = lat0.to_radians();
+                let lat = (c.cos() * lat0.sin() + y * c.sin() * lat0.cos() / rho).asin();
+                let lon = f64::atan2(
+                    x * c.sin(),
+                    rho * c.cos() * lat0.cos() - y * c.sin() * lat0.sin(),
+                );
+                Some((lon0 + lon.to_degrees(), lat.to_degrees()))
+            }
+        }
+    }
+}
+
+/// Ways of writing a bounding box for other GIS tools
+#[derive(Debug, Clone, Default, Copy, Eq, PartialEq, Hash)]
+pub enum BboxFormat {
+    /// `minlon,minlat,maxlon,maxlat`
+    #[default]
+    Plain,
+    /// `[minlon, minlat, maxlon, maxlat]` as a GeoJSON `bbox` member
+    GeoJson,
+    Wkt,
+}
+
+impl BboxFormat {
+    pub fn next(self) -> Self {
+        match self {
+            Self::Plain => Self::GeoJson,
+            Self::GeoJson => Self::Wkt,
+            Self::Wkt => Self::Plain,
+        }
+    }
+
+    /// Formats `[min_lon, min_lat, max_lon, max_lat]` with `precision` decimals
+    pub fn format(self, bounds: [f64; 4], precision: usize) -> String {
+        let [min_x, min_y, max_x, max_y] = bounds.map(|value| format!("{value:.precision$}"));
+        match self {
+            Self::Plain => format!("{min_x},{min_y},{max_x},{max_y}"),
+            Self::GeoJson => format!("[{min_x}, {min_y}, {max_x}, {max_y}]"),
+            Self::Wkt => format!(
+                "POLYGON (({min_x} {min_y}, {max_x} {min_y}, {max_x} {max_y}, \
+                 {min_x} {max_y}, {min_x} {min_y}))"
+            ),
+        }
+    }
+}
+
+/// Longitude of `lon` east of `prime_meridian`, wrapped to -180..=180
+pub fn relative_longitude(lon: f64, prime_meridian: f64) -> f64 {
+    let lon = lon - prime_meridian;
+    if (-180.0..=180.).contains(&lon) {
+        lon
+    } else {
+        (lon + 180.).rem_euclid(360.) - 180.
+    }
+}
+
+/// Round interval (1, 2 or 5 times a power of ten) splitting `span` into about
+/// `count` parts
+pub fn nice_step(span: f64, count: f64) -> f64 {
+    let raw = span / count;
+    let magnitude = 10_f64.powf(raw.log10().floor());
+    let step = match raw / magnitude {
+        r if r < 1.5 => 1.,
+        r if r < 3.5 => 2.,
+        r if r < 7.5 => 5.,
+        _ => 10.,
+    };
+    step * magnitude
+}
+
+/// Graticule spacing in degrees for a view `span` degrees wide, the widest of
+/// `widest` and the usual finer spacings that still draws a few lines
+pub fn graticule_step(span: f64, widest: f64) -> f64 {
+    let finer = [30., 10., 5., 1., 0.5]
+        .into_iter()
+        .filter(|step| *step < widest);
+    std::iter::once(widest)
+        .chain(finer)
+        .find(|step| span / step >= 3.)
+        .unwrap_or_else(|| nice_step(span, 4.))
+}
+
+/// Position reached travelling `distance_km` from (lon, lat) along a great circle
+/// starting at `bearing` degrees clockwise from north
+pub fn destination(lon: f64, lat: f64, bearing: f64, distance_km: f64) -> (f64, f64) {
+    let (lon, lat, bearing) = (lon.to_radians(), lat.to_radians(), bearing.to_radians());
+    let angle = distance_km / EARTH_RADIUS_KM;
+    let end_lat = (lat.sin() * angle.cos() + lat.cos() * angle.sin() * bearing.cos()).asin();
+    let end_lon = lon
+        + (bearing.sin() * angle.sin() * lat.cos()).atan2(angle.cos() - lat.sin() * end_lat.sin());
+    // wrap back into -180..180
+    let end_lon = (end_lon.to_degrees() + 540.).rem_euclid(360.) - 180.;
+    (end_lon, end_lat.to_degrees())
+}
+
+/// Points `radius_km` away from (lon, lat) sampled at `segments` bearings, split into
+/// several lines where the circle crosses the antimeridian
+pub fn circle(lon: f64, lat: f64, radius_km: f64, segments: usize) -> Vec<Polyline> {
+    let mut lines = vec![Polyline::new()];
+    for i in 0..=segments {
+        let bearing = 360. * i as f64 / segments as f64;
+        let point = destination(lon, lat, bearing, radius_km);
+        let line = lines.last_mut().expect("lines starts with one line");
+        if line
+            .last()
+            .is_some_and(|last| (last.0 - point.0).abs() > 180.)
+        {
+            lines.push(vec![point]);
+        } else {
+            line.push(point);
+        }
+    }
+    lines
+}
+
+/// Mean position of `points`. Longitudes are averaged as angles, so points on both
+/// sides of the antimeridian average to it rather than to the prime meridian
+pub fn centroid(points: impl IntoIterator<Item = (f64, f64)>) -> Option<(f64, f64)> {
+    let (mut sin, mut cos, mut lat, mut count) = (0., 0., 0., 0);
+    for (lon, point_lat) in points {
+        sin += lon.to_radians().sin();
+        cos += lon.to_radians().cos();
+        lat += point_lat;
+        count += 1;
+    }
+    (count > 0).then(|| (f64::atan2(sin, cos).to_degrees(), lat / f64::from(count)))
+}
+
+/// Part of the segment from `a` to `b` within `[min_x, min_y, max_x, max_y]`, `None`
+/// if it misses the box (Liang-Barsky)
+pub fn clip_segment(
+    a: (f64, f64),
+    b: (f64, f64),
+    [min_x, min_y, max_x, max_y]: [f64; 4],
+) -> Option<((f64, f64), (f64, f64))> {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let (mut enter, mut exit) = (0_f64, 1_f64);
+    for (p, q) in [
+        (-dx, a.0 - min_x),
+        (dx, max_x - a.0),
+        (-dy, a.1 - min_y),
+        (dy, max_y - a.1),
+    ] {
+        if p == 0. {
+            // parallel to this edge, and outside of it
+            if q < 0. {
+                return None;
+            }
+        } else if p < 0. {
+            enter = enter.max(q / p);
+        } else {
+            exit = exit.min(q / p);
+        }
+    }
+    (enter <= exit).then_some((
+        (a.0 + enter * dx, a.1 + enter * dy),
+        (a.0 + exit * dx, a.1 + exit * dy),
+    ))
+}
+
+/// Decimals needed to tell apart values a `step` apart
+pub fn step_precision(step: f64) -> usize {
+    (-step.log10()).ceil().max(0.) as usize
+}
+
+/// Six character Maidenhead locator (field, square and subsquare) of a coordinate
+pub fn maidenhead(lon: f64, lat: f64) -> String {
+    // wrap a turn or more around the world back in, shift to positive ranges and
+    // keep the poles and antimeridian inside the last cell
+    let mut lon = (relative_longitude(lon, 0.) + 180.).clamp(0., 360. - 1e-9);
+    let mut lat = (lat + 90.).clamp(0., 180. - 1e-9);
+    let mut locator = String::with_capacity(6);
+    // (cell width, cell height, first symbol) for field, square and subsquare
+    for (width, height, base) in [(20., 10., b'A'), (2., 1., b'0'), (2. / 24., 1. / 24., b'a')] {
+        locator.push(char::from(base + (lon / width) as u8));
+        locator.push(char::from(base + (lat / height) as u8));
+        lon %= width;
+        lat %= height;
+    }
+    locator
+}
+
+/// Position in the Universal Transverse Mercator system
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Utm {
+    pub zone: u8,
+    /// latitude band letter
+    pub band: char,
+    /// meters
+    pub easting: f64,
+    /// meters, offset by 10000 km in the southern hemisphere
+    pub northing: f64,
+}
+
+impl fmt::Display for Utm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}{} {:.0}E {:.0}N",
+            self.zone, self.band, self.easting, self.northing
+        )
+    }
+}
+
+/// UTM position of a WGS84 coordinate, or `None` near the poles where UTM is not
+/// defined (beyond 80°S and 84°N)
+pub fn utm(lon: f64, lat: f64) -> Option<Utm> {
+    if !(-80. ..=84.).contains(&lat) {
+        return None;
+    }
+    const A: f64 = 6_378_137.;
+    const F: f64 = 1. / 298.257_223_563;
+    const K0: f64 = 0.9996;
+    let e2 = F * (2. - F);
+    let (e4, e6) = (e2 * e2, e2 * e2 * e2);
+    let ep2 = e2 / (1. - e2);
+
+    let lon = if lon >= 180. { lon - 360. } else { lon };
+    let mut zone = ((lon + 180.) / 6.).floor() as u8 + 1;
+    // zones widened over southwest Norway and Svalbard
+    if (56. ..64.).contains(&lat) && (3. ..12.).contains(&lon) {
+        zone = 32;
+    } else if (72. ..=84.).contains(&lat) && (0. ..42.).contains(&lon) {
+        zone = match lon {
+            lon if lon < 9. => 31,
+            lon if lon < 21. => 33,
+            lon if lon < 33. => 35,
+            _ => 37,
+        };
+    }
+    let band = b"CDEFGHJKLMNPQRSTUVWXX"[((lat + 80.) / 8.) as usize] as char;
+
+    let central_meridian = (f64::from(zone) * 6. - 183.).to_radians();
+    let phi = lat.to_radians();
+    let n = A / (1. - e2 * phi.sin().powi(2)).sqrt();
+    let t = phi.tan().powi(2);
+    let c = ep2 * phi.cos().powi(2);
+    let a = phi.cos() * (lon.to_radians() - central_meridian);
+    // meridian arc length from the equator
+    let m = A
+        * ((1. - e2 / 4. - 3. * e4 / 64. - 5. * e6 / 256.) * phi
+            - (3. * e2 / 8. + 3. * e4 / 32. + 45. * e6 / 1024.) * (2. * phi).sin()
+            + (15. * e4 / 256. + 45. * e6 / 1024.) * (4. * phi).sin()
+            - (35. * e6 / 3072.) * (6. * phi).sin());
+
+    let easting = K0
+        * n
+        * (a + (1. - t + c) * a.powi(3) / 6.
+            + (5. - 18. * t + t * t + 72. * c - 58. * ep2) * a.powi(5) / 120.)
+        + 500_000.;
+    let mut northing = K0
+        * (m + n
+            * phi.tan()
+            * (a * a / 2.
+                + (5. - t + 9. * c + 4. * c * c) * a.powi(4) / 24.
+                + (61. - 58. * t + t * t + 600. * c - 330. * ep2) * a.powi(6) / 720.));
+    if lat < 0. {
+        northing += 10_000_000.;
+    }
+    Some(Utm {
+        zone,
+        band,
+        easting,
+        northing,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maidenhead_known_locators() {
+        assert_eq!(maidenhead(-0.0015, 51.4779), "IO91xl"); // Greenwich
+        assert_eq!(maidenhead(-77.0365, 38.8977), "FM18lv"); // Washington DC
+        assert_eq!(maidenhead(151.2093, -33.8688), "QF56od"); // Sydney
+    }
+
+    #[test]
+    fn maidenhead_extremes() {
+        assert_eq!(maidenhead(-180., -90.), "AA00aa");
+        assert_eq!(maidenhead(180., 90.), "RR99xx");
+        // a view panned across the antimeridian reads longitudes past it
+        assert_eq!(maidenhead(190., 0.), maidenhead(-170., 0.));
+        assert_eq!(maidenhead(-77.0365 + 720., 38.8977), "FM18lv");
+    }
+
+    #[test]
+    fn relative_longitudes() {
+        assert_eq!(relative_longitude(180., 0.), 180.);
+        assert_eq!(relative_longitude(2., 2.), 0.);
+        // west of a prime meridian at 170 wraps around the antimeridian
+        assert_eq!(relative_longitude(-170., 170.), 20.);
+        assert_eq!(relative_longitude(170., -170.), -20.);
+    }
+
+    #[test]
+    fn bbox_formats() {
+        let bounds = [-10., -5.5, 20., 30.25];
+        assert_eq!(BboxFormat::Plain.format(bounds, 1), "-10.0,-5.5,20.0,30.2");
+        assert_eq!(BboxFormat::GeoJson.format(bounds, 0), "[-10, -6, 20, 30]");
+        assert_eq!(
+            BboxFormat::Wkt.format(bounds, 0),
+            "POLYGON ((-10 -6, 20 -6, 20 30, -10 30, -10 -6))"
+        );
+    }
+
+    #[test]
+    fn mercator() {
+        let mercator = Projection::Mercator;
+        assert!(mercator.y(0.).abs() < 1e-9);
+        // stretched more and more towards the poles
+        assert!((mercator.y(1.) - 1.).abs() < 0.01);
+        assert!(mercator.y(60.) > 2. * mercator.y(30.));
+        assert!((mercator.latitude(mercator.y(51.5)) - 51.5).abs() < 1e-9);
+        assert_eq!(mercator.point(10., 89., (0., 0.)), None);
+        assert_eq!(
+            Projection::Equirectangular.point(10., 89., (0., 0.)),
+            Some((10., 89.))
+        );
+    }
+
+    #[test]
+    fn orthographic() {
+        let globe = Projection::Orthographic;
+        let center = (-3.7, 40.4);
+        assert_eq!(globe.point(-3.7, 40.4, center), Some((0., 0.)));
+        // the far side is hidden
+        assert_eq!(globe.point(176.3, -40.4, center), None);
+        // a degree near the center is about one unit
+        let (x, y) = globe.point(-2.7, 40.4, center).unwrap();
+        assert!(x > 0.7 && x < 0.8 && y.abs() < 0.01, "{x} {y}");
+
+        for (lon, lat) in [(0., 51.5), (-74., 40.7), (-3.7, 89.)] {
+            let (x, y) = globe.point(lon, lat, center).unwrap();
+            let (back_lon, back_lat) = globe.coordinate(x, y, center).unwrap();
+            assert!((back_lon - lon).abs() < 1e-6 && (back_lat - lat).abs() < 1e-6);
+        }
+        assert_eq!(globe.coordinate(60., 0., center), None);
+    }
+
+    #[test]
+    fn nice_steps() {
+        assert_eq!(nice_step(360., 8.), 50.);
+        assert_eq!(nice_step(10., 5.), 2.);
+        assert_eq!(nice_step(0.3, 6.), 0.05);
+        assert_eq!(step_precision(50.), 0);
+        assert_eq!(step_precision(0.05), 2);
+    }
+
+    #[test]
+    fn clip_segments() {
+        let bounds = [0., 0., 10., 10.];
+        assert_eq!(
+            clip_segment((-5., 5.), (15., 5.), bounds),
+            Some(((0., 5.), (10., 5.)))
+        );
+        assert_eq!(
+            clip_segment((2., 2.), (4., 4.), bounds),
+            Some(((2., 2.), (4., 4.)))
+        );
+        assert_eq!(
+            clip_segment((5., -5.), (5., 5.), bounds),
+            Some(((5., 0.), (5., 5.)))
+        );
+        // passes by the corner
+        assert_eq!(clip_segment((-5., 0.), (0., -5.), bounds), None);
+        assert_eq!(clip_segment((-5., 20.), (20., 15.), bounds), None);
+    }
+
+    #[test]
+    fn graticule_steps() {
+        assert_eq!(graticule_step(360., 30.), 30.);
+        assert_eq!(graticule_step(60., 30.), 10.);
+        assert_eq!(graticule_step(20., 30.), 5.);
+        assert_eq!(graticule_step(4., 30.), 1.);
+        assert_eq!(graticule_step(1., 30.), 0.2);
+        assert_eq!(graticule_step(360., 15.), 15.);
+        assert_eq!(graticule_step(360., 5.), 5.);
+    }
+
+    #[test]
+    fn destinations() {
+        let (lon, lat) = destination(0., 0., 0., KM_PER_DEGREE);
+        assert!(lon.abs() < 1e-9 && (lat - 1.).abs() < 1e-9);
+        // a quarter of the way around the equator
+        let (lon, lat) = destination(0., 0., 90., KM_PER_DEGREE * 90.);
+        assert!((lon - 90.).abs() < 1e-9 && lat.abs() < 1e-9);
+        let (lon, _) = destination(179.5, 0., 90., KM_PER_DEGREE);
+        assert!((lon + 179.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn circle_splits_at_antimeridian() {
+        assert_eq!(circle(0., 0., 500., 36).len(), 1);
+        let lines = circle(180., 0., 500., 36);
+        assert!(lines.len() > 1);
+        for line in lines {
+            for pair in line.windows(2) {
+                assert!((pair[0].0 - pair[1].0).abs() < 180.);
+            }
+        }
+    }
+
+    #[test]
+    fn centroids() {
+        assert_eq!(centroid([]), None);
+        let (lon, lat) = centroid([(10., 0.), (20., 10.)]).unwrap();
+        assert!((lon - 15.).abs() < 1e-9 && (lat - 5.).abs() < 1e-9);
+        let (lon, _) = centroid([(170., 0.), (-170., 0.)]).unwrap();
+        assert!((lon.abs() - 180.).abs() < 1e-9);
+    }
+
+    #[test]
+    fn utm_known_positions() {
+        // Empire State Building
+        assert_eq!(
+            utm(-73.9857, 40.7484).unwrap().to_string(),
+            "18T 585628E 4511322N"
+        );
+        // Eiffel Tower
+        assert_eq!(
+            utm(2.2945, 48.8584).unwrap().to_string(),
+            "31U 448252E 5411955N"
+        );
+        // Sydney, southern hemisphere
+        assert_eq!(
+            utm(151.2093, -33.8688).unwrap().to_string(),
+            "56H 334369E 6250948N"
+        );
+    }
+
+    #[test]
+    fn utm_zone_exceptions_and_poles() {
+        assert_eq!(utm(5., 60.).unwrap().zone, 32); // Bergen
+        assert_eq!(utm(15., 78.).unwrap().zone, 33); // Svalbard
+        assert_eq!(utm(0., 85.), None);
+        assert_eq!(utm(0., -81.), None);
+    }
+}
@@ -0,0 +1,156 @@
+use std::fmt;
+
+use serde::{
+    de::{self, IgnoredAny, SeqAccess, Visitor},
+    Deserialize, Deserializer,
+};
+
+use crate::map::Polyline;
+
+/// Any GeoJSON object, features and collections nest the geometries
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type")]
+enum Object {
+    FeatureCollection {
+        features: Vec<Object>,
+    },
+    /// a feature without a location has a null geometry
+    Feature {
+        geometry: Option<Box<Object>>,
+    },
+    GeometryCollection {
+        geometries: Vec<Object>,
+    },
+    Point {
+        coordinates: Position,
+    },
+    MultiPoint {
+        coordinates: Vec<Position>,
+    },
+    LineString {
+        coordinates: Vec<Position>,
+    },
+    MultiLineString {
+        coordinates: Vec<Vec<Position>>,
+    },
+    Polygon {
+        coordinates: Vec<Vec<Position>>,
+    },
+    MultiPolygon {
+        coordinates: Vec<Vec<Vec<Position>>>,
+    },
+}
+
+/// `[lon, lat]`, ignoring any altitude
+#[derive(Debug, Clone, Copy)]
+struct Position(f64, f64);
+
+impl<'de> Deserialize<'de> for Position {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct PositionVisitor;
+
+        impl<'de> Visitor<'de> for PositionVisitor {
+            type Value = Position;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a position such as [lon, lat]")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Position, A::Error> {
+                let lon = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+                let lat = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+                while seq.next_element::<IgnoredAny>()?.is_some() {}
+                Ok(Position(lon, lat))
+            }
+        }
+
+        deserializer.deserialize_seq(PositionVisitor)
+    }
+}
+
+fn sequence(positions: Vec<Position>) -> Polyline {
+    positions.into_iter().map(|Position(x, y)| (x, y)).collect()
+}
+
+impl Object {
+    /// Lines and polygon rings as polylines, points as polylines of a single position
+    fn into_polylines(self, polylines: &mut Vec<Polyline>) {
+        match self {
+            Self::FeatureCollection { features: objects }
+            | Self::GeometryCollection {
+                geometries: objects,
+            } => {
+                for object in objects {
+                    object.into_polylines(polylines);
+                }
+            }
+            Self::Feature { geometry } => {
+                if let Some(geometry) = geometry {
+                    geometry.into_polylines(polylines);
+                }
+            }
+            Self::Point { coordinates } => polylines.push(sequence(vec![coordinates])),
+            Self::MultiPoint { coordinates } => polylines.extend(
+                coordinates
+                    .into_iter()
+                    .map(|position| sequence(vec![position])),
+            ),
+            Self::LineString { coordinates } => polylines.push(sequence(coordinates)),
+            Self::MultiLineString { coordinates } | Self::Polygon { coordinates } => {
+                polylines.extend(coordinates.into_iter().map(sequence))
+            }
+            Self::MultiPolygon { coordinates } => {
+                polylines.extend(coordinates.into_iter().flatten().map(sequence))
+            }
+        }
+    }
+}
+
+/// Parses a GeoJSON document into polylines of (lon, lat). Points become polylines of
+/// a single position, polygons one polyline per ring
+pub fn parse(input: &str) -> serde_json::Result<Vec<Polyline>> {
+    let object: Object = serde_json::from_str(input)?;
+    let mut polylines = Vec::new();
+    object.into_polylines(&mut polylines);
+    Ok(polylines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_nested_geometries() {
+        let polylines = parse(
+            r#"{
+                "type": "FeatureCollection",
+                "features": [
+                    {"type": "Feature", "properties": {"name": "a"},
+                     "geometry": {"type": "Point", "coordinates": [2.35, 48.85, 35]}},
+                    {"type": "Feature", "properties": null, "geometry": null},
+                    {"type": "Feature", "geometry": {"type": "MultiPolygon", "coordinates": [
+                        [[[0, 0], [1, 0], [1, 1], [0, 0]], [[0.2, 0.2], [0.4, 0.2], [0.2, 0.2]]],
+                        [[[5, 5], [6, 5], [5, 5]]]
+                    ]}}
+                ]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(polylines.len(), 4);
+        assert_eq!(polylines[0], vec![(2.35, 48.85)]);
+        assert_eq!(polylines[1].len(), 4);
+        assert_eq!(polylines[3], vec![(5., 5.), (6., 5.), (5., 5.)]);
+    }
+
+    #[test]
+    fn rejects_malformed_input() {
+        assert!(parse(r#"{"type": "LineString", "coordinates": [[0, 0], [1]]}"#).is_err());
+        assert!(parse(r#"{"type": "Circle", "coordinates": [0, 0]}"#).is_err());
+        let err = parse("{\"type\": \"Point\",\n\"coordinates\": [0, a]}").unwrap_err();
+        assert_eq!(err.line(), 2);
+    }
+}
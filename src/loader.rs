@@ -0,0 +1,335 @@
+use std::{
+    fmt, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    cli::Mark,
+    geojson,
+    manifest::Manifest,
+    map::{Points, Polyline},
+    wkt::{self, Wkt},
+};
+
+/// Failure to load a data file given by the user
+#[derive(Debug)]
+pub enum LoadError {
+    /// the file could not be read
+    Io { path: PathBuf, source: io::Error },
+    /// the file is in the expected format but its contents are malformed
+    Parse {
+        path: PathBuf,
+        line: usize,
+        reason: String,
+    },
+    /// the contents don't look like the expected format at all
+    Format {
+        path: PathBuf,
+        expected: &'static str,
+    },
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io { path, source } => write!(f, "failed to read {}: {source}", path.display()),
+            Self::Parse { path, line, reason } => {
+                write!(f, "{}:{line}: {reason}", path.display())
+            }
+            Self::Format { path, expected } => {
+                write!(f, "{} does not look like {expected}", path.display())
+            }
+        }
+    }
+}
+
+impl std::error::Error for LoadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+fn read(path: &Path) -> Result<String, LoadError> {
+    std::fs::read_to_string(path).map_err(|source| LoadError::Io {
+        path: path.to_owned(),
+        source,
+    })
+}
+
+/// 1-based line number of a byte offset
+fn line_at(input: &str, pos: usize) -> usize {
+    input[..pos.min(input.len())].matches('\n').count() + 1
+}
+
+pub fn load_wkt(path: &Path) -> Result<Wkt, LoadError> {
+    let input = read(path)?;
+    // every WKT geometry (and the EWKT SRID prefix) starts with a tag
+    if input
+        .trim_start()
+        .starts_with(|c: char| !c.is_ascii_alphabetic())
+    {
+        return Err(LoadError::Format {
+            path: path.to_owned(),
+            expected: "WKT",
+        });
+    }
+    wkt::parse(&input).map_err(|err| LoadError::Parse {
+        path: path.to_owned(),
+        line: line_at(&input, err.pos),
+        reason: err.reason,
+    })
+}
+
+pub fn load_geojson(path: &Path) -> Result<Vec<Polyline>, LoadError> {
+    let input = read(path)?;
+    // every GeoJSON document is a single object
+    if !input.trim_start().starts_with('{') {
+        return Err(LoadError::Format {
+            path: path.to_owned(),
+            expected: "GeoJSON",
+        });
+    }
+    geojson::parse(&input).map_err(|err| {
+        // the position is reported on its own
+        let reason = err.to_string();
+        let reason = reason.split(" at line ").next().unwrap_or_default();
+        LoadError::Parse {
+            path: path.to_owned(),
+            // only syntax errors are located, not those in the structure of an object
+            line: err.line().max(1),
+            reason: reason.to_string(),
+        }
+    })
+}
+
+/// Reads `lat lon` lines, separated by whitespace or a comma, in the format of the
+/// baked datasets. A blank line starts a new path and a header line is skipped
+pub fn load_points(path: &Path) -> Result<Points, LoadError> {
+    let input = read(path)?;
+    let mut points = Points::default();
+    let mut new_path = false;
+    for (index, line) in input.lines().enumerate() {
+        if line.trim().is_empty() {
+            new_path = true;
+            continue;
+        }
+        let mut parts = line
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|part| !part.is_empty());
+        let (Some(lat), Some(lon)) = (parts.next(), parts.next()) else {
+            return Err(LoadError::Parse {
+                path: path.to_owned(),
+                line: index + 1,
+                reason: format!("expected `lat lon` but found `{line}`"),
+            });
+        };
+        let (Ok(lat), Ok(lon)) = (lat.parse::<f64>(), lon.parse::<f64>()) else {
+            // a header such as `lat,lon`
+            if index == 0 {
+                continue;
+            }
+            return Err(LoadError::Parse {
+                path: path.to_owned(),
+                line: index + 1,
+                reason: format!("invalid coordinates `{line}`"),
+            });
+        };
+        if lat.abs() > 90. || lon.abs() > 360. {
+            return Err(LoadError::Parse {
+                path: path.to_owned(),
+                line: index + 1,
+                reason: format!("`{line}` is out of range, expected latitude first"),
+            });
+        }
+        if new_path && !points.coordinates.is_empty() {
+            points.breaks.push(points.coordinates.len());
+        }
+        new_path = false;
+        points.coordinates.push((lon, lat));
+    }
+    if points.coordinates.is_empty() {
+        return Err(LoadError::Format {
+            path: path.to_owned(),
+            expected: "lat lon coordinates",
+        });
+    }
+    Ok(points)
+}
+
+/// Reads `lon,lat,label` CSV rows, the label being optional. A header row is skipped
+pub fn load_marks(path: &Path) -> Result<Vec<Mark>, LoadError> {
+    let input = read(path)?;
+    let mut marks = Vec::new();
+    for (index, line) in input.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let error = |reason: String| LoadError::Parse {
+            path: path.to_owned(),
+            line: index + 1,
+            reason,
+        };
+        // labels may hold commas of their own
+        let mut fields = line.splitn(3, ',').map(str::trim);
+        let (Some(lon), Some(lat)) = (fields.next(), fields.next()) else {
+            return Err(error(format!(
+                "expected `lon,lat,label` but found `{line}`"
+            )));
+        };
+        let (Ok(lon), Ok(lat)) = (lon.parse::<f64>(), lat.parse::<f64>()) else {
+            // a header such as `lon,lat,label`
+            if index == 0 {
+                continue;
+            }
+            return Err(error(format!("invalid coordinates `{line}`")));
+        };
+        if lat.abs() > 90. {
+            return Err(error(format!(
+                "`{line}` is out of range, expected longitude first"
+            )));
+        }
+        let label = fields
+            .next()
+            .filter(|label| !label.is_empty())
+            .map(|label| label.trim_matches('"').to_string());
+        marks.push(Mark { lon, lat, label });
+    }
+    Ok(marks)
+}
+
+pub fn load_manifest(path: &Path) -> Result<Manifest, LoadError> {
+    let input = read(path)?;
+    toml::from_str(&input).map_err(|err| LoadError::Parse {
+        path: path.to_owned(),
+        line: err.span().map_or(1, |span| line_at(&input, span.start)),
+        reason: err.message().to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_temp(name: &str, contents: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("plou-loader-{name}"));
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn io_error() {
+        let err = load_wkt(Path::new("/nonexistent/lines.wkt")).unwrap_err();
+        assert!(matches!(err, LoadError::Io { .. }));
+        assert!(err.to_string().contains("/nonexistent/lines.wkt"));
+    }
+
+    #[test]
+    fn parse_error_reports_line() {
+        let path = write_temp(
+            "parse.wkt",
+            "LINESTRING (0 0, 1 1)\nLINESTRING (0 0, x 1)\n",
+        );
+        match load_wkt(&path).unwrap_err() {
+            LoadError::Parse { line, .. } => assert_eq!(line, 2),
+            err => panic!("unexpected error {err:?}"),
+        }
+    }
+
+    #[test]
+    fn format_error() {
+        let path = write_temp("format.wkt", r#"{"type": "FeatureCollection"}"#);
+        let err = load_wkt(&path).unwrap_err();
+        assert!(matches!(
+            err,
+            LoadError::Format {
+                expected: "WKT",
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn load_geojson_file() {
+        let path = write_temp(
+            "lines.geojson",
+            r#"{"type": "LineString", "coordinates": [[0, 0], [1, 1]]}"#,
+        );
+        assert_eq!(load_geojson(&path).unwrap(), vec![vec![(0., 0.), (1., 1.)]]);
+
+        let path = write_temp(
+            "bad.geojson",
+            "{\n\"type\": \"Polygon\",\n\"coordinates\": [[0 0]]}",
+        );
+        let err = load_geojson(&path).unwrap_err();
+        assert!(matches!(err, LoadError::Parse { line: 3, .. }), "{err}");
+        assert!(!err.to_string().contains("column"));
+
+        let path = write_temp(
+            "polygon.geojson",
+            r#"{"type": "Polygon", "coordinates": 3}"#,
+        );
+        assert!(matches!(
+            load_geojson(&path).unwrap_err(),
+            LoadError::Parse { line: 1, .. }
+        ));
+    }
+
+    #[test]
+    fn load_points_paths() {
+        let path = write_temp("points.txt", "lat,lon\n10 20\n11 21\n\n-5,30\n");
+        let points = load_points(&path).unwrap();
+        assert_eq!(points.coordinates, vec![(20., 10.), (21., 11.), (30., -5.)]);
+        assert_eq!(points.breaks, vec![2]);
+
+        let path = write_temp("bad-points.txt", "10 20\n11 x\n");
+        match load_points(&path).unwrap_err() {
+            LoadError::Parse { line, .. } => assert_eq!(line, 2),
+            err => panic!("unexpected error {err:?}"),
+        }
+    }
+
+    #[test]
+    fn reject_swapped_or_missing_points() {
+        let path = write_temp("swapped-points.txt", "10 20\n\n120 40\n");
+        let err = load_points(&path).unwrap_err();
+        assert!(matches!(err, LoadError::Parse { line: 3, .. }));
+        assert!(err.to_string().contains("latitude first"));
+
+        let path = write_temp("empty-points.txt", "lat lon\n\n");
+        assert!(matches!(
+            load_points(&path).unwrap_err(),
+            LoadError::Format { .. }
+        ));
+    }
+
+    #[test]
+    fn load_marks_csv() {
+        let path = write_temp(
+            "marks.csv",
+            "lon,lat,label\n-3.7,40.4,Madrid\n2.35,48.85\n-74,40.7,\"New York, NY\"\n",
+        );
+        let marks = load_marks(&path).unwrap();
+        assert_eq!(marks.len(), 3);
+        assert_eq!(marks[0].label.as_deref(), Some("Madrid"));
+        assert_eq!(
+            (marks[1].lon, marks[1].lat, marks[1].label.clone()),
+            (2.35, 48.85, None)
+        );
+        assert_eq!(marks[2].label.as_deref(), Some("New York, NY"));
+
+        let path = write_temp("bad-marks.csv", "-3.7,40.4\n40.4\n");
+        assert!(matches!(
+            load_marks(&path).unwrap_err(),
+            LoadError::Parse { line: 2, .. }
+        ));
+    }
+
+    #[test]
+    fn loads_valid_file() {
+        let path = write_temp("valid.wkt", "LINESTRING (0 0, 1 1)\n");
+        assert_eq!(load_wkt(&path).unwrap().polylines.len(), 1);
+    }
+}
@@ -1,8 +1,9 @@
+use cli::{KeyBinding, Layer, Mark, ResetTarget};
 use crossterm::event::{MouseButton, MouseEvent, MouseEventKind};
-use map::WorldMap;
+use map::{LayerData, LineFormat, MapLayer, Points, WorldMap, WorldResolution};
 use ratatui::{
     buffer::Buffer,
-    crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind},
+    crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
     layout::{Alignment, Rect},
     style::Stylize,
     symbols::border,
@@ -17,33 +18,108 @@ use ratatui::{
 
 // How many map units are moved per step of zoom
 const ZOOM_STEP_SIZE: f64 = 0.2;
-const PAN_STEP_SIZE: i32 = 100;
+/// Narrowest span in degrees the view can be zoomed into, on either axis
+const MIN_SPAN: f64 = 0.5;
+/// Widest span in degrees the view can be zoomed out to, the whole world
+const MAX_SPAN: (f64, f64) = (360., 180.);
+/// Share of the span kept by one scroll wheel step zooming in
+const SCROLL_ZOOM_FACTOR: f64 = 0.9;
+/// Fraction of the visible span moved by one pan step
+const PAN_STEP_FRACTION: f64 = 0.1;
+/// How long after the last movement the view is considered settled
+const SETTLE_TIME: Duration = Duration::from_millis(300);
+/// Degrees a simplified coastline may stray from the full one when
+/// `--simplify-tolerance` isn't given
+const SIMPLIFY_TOLERANCE: f64 = 0.1;
+/// Time between two zoom steps while a zoom key is held
+const ZOOM_TICK: Duration = Duration::from_millis(30);
+/// How long blinking markers stay shown, and then hidden
+const BLINK_INTERVAL: Duration = Duration::from_millis(500);
+/// Graticule spacing in degrees when zoomed out, if `--graticule-step` isn't given
+const GRATICULE_STEP: f64 = 30.;
+/// Decimals of the coordinates shown when `--precision` isn't given
+const DEFAULT_PRECISION: usize = 5;
+/// Redraws per second when `--max-fps` isn't given
+const DEFAULT_MAX_FPS: u32 = 60;
+/// How much of the brightness is taken away in the corners by the vignette
+const VIGNETTE_STRENGTH: f64 = 0.7;
+/// Where the current layer stack is exported to, in the working directory
+const MANIFEST_EXPORT_PATH: &str = "plou.toml";
+/// How many cells away from the cursor a coastline point is still highlighted
+const NEAREST_POINT_CELLS: f64 = 2.;
 
 use color_eyre::{eyre::WrapErr, Result};
+use geo::{BboxFormat, CoordinateFormat, Projection};
+use manifest::Manifest;
+use std::{
+    cell::{Cell, OnceCell},
+    io::{self, Write},
+    path::Path,
+    time::{Duration, Instant},
+};
 
+mod cli;
+mod geo;
+mod geojson;
+mod loader;
+mod manifest;
 mod map;
+mod state;
 mod tui;
+mod wkt;
 
 fn main() -> Result<()> {
     color_eyre::install()?;
+    let args = cli::Args::parse()?;
+    if args.version {
+        println!("plou {}", env!("CARGO_PKG_VERSION"));
+        println!(
+            "world data (low): {} ({} points)",
+            env!("PLOU_WORLD_SRC_LOW"),
+            env!("PLOU_WORLD_POINTS_LOW")
+        );
+        println!(
+            "world data (med): {} ({} points)",
+            env!("PLOU_WORLD_SRC_MED"),
+            env!("PLOU_WORLD_POINTS_MED")
+        );
+        println!(
+            "world data (high): {} ({} points)",
+            env!("PLOU_WORLD_SRC_HIGH"),
+            env!("PLOU_WORLD_POINTS_HIGH")
+        );
+        return Ok(());
+    }
+    let mut app = App::new(args)?;
     let mut terminal = tui::init()?;
-    let app_result = App::default().run(&mut terminal);
+    app.continuous_zoom = tui::enable_key_release_events()?;
+    let app_result = app.run(&mut terminal);
     if let Err(err) = tui::restore() {
         eprintln!(
             "failed to restore the terminal. Run `reset` or restart your terminal to recover: {}",
             err
         );
     }
+    if app_result.is_ok() {
+        // the next launch starts where this one left off
+        if let Some(path) = state::view_path() {
+            if let Err(err) = state::save_view(&path, app.viewport().bounds()) {
+                eprintln!("failed to save the view to {}: {err}", path.display());
+            }
+        }
+    }
     app_result
 }
 
-#[derive(Debug)]
-struct Viewport {
-    min_x: f64,
-    max_x: f64,
-    min_y: f64,
-    max_y: f64,
-    zoom_level: f64,
+#[derive(Debug, Clone, PartialEq)]
+pub struct Viewport {
+    pub min_x: f64,
+    pub max_x: f64,
+    pub min_y: f64,
+    pub max_y: f64,
+    pub zoom_level: f64,
+    /// how the latitudes between `min_y` and `max_y` are laid out on the canvas
+    pub projection: Projection,
 }
 
 impl Default for Viewport {
@@ -58,33 +134,566 @@ impl Default for Viewport {
             max_y: 90.,
             // max_y: 100.,
             zoom_level: 0.,
+            projection: Projection::default(),
         }
     }
 }
 
 impl Viewport {
-    fn zoom(&mut self, z: i32) {
-        self.zoom_level += f64::from(z) * ZOOM_STEP_SIZE;
-        self.min_x += f64::from(z) * ZOOM_STEP_SIZE;
-        self.min_y += f64::from(z) * ZOOM_STEP_SIZE / 2;
-        self.max_x -= f64::from(z) * ZOOM_STEP_SIZE;
-        self.max_y -= (f64::from(z) * ZOOM_STEP_SIZE / 2);
+    /// Viewport framing the given bounds
+    fn from_bounds(min_x: f64, min_y: f64, max_x: f64, max_y: f64) -> Self {
+        Self {
+            min_x,
+            max_x,
+            min_y,
+            max_y,
+            // the zoom level counts the units shrunk on each side of the world extent
+            zoom_level: (360. - (max_x - min_x)) / 2.,
+            projection: Projection::default(),
+        }
+    }
+
+    /// Viewport framing the given bounds with some padding, so nothing touches the
+    /// border
+    fn fit_bounds(min_x: f64, min_y: f64, max_x: f64, max_y: f64) -> Self {
+        let pad_x = ((max_x - min_x) * 0.05).max(0.5);
+        let pad_y = ((max_y - min_y) * 0.05).max(0.5);
+        Self::from_bounds(min_x - pad_x, min_y - pad_y, max_x + pad_x, max_y + pad_y)
+    }
+
+    /// Coordinate at the center of the terminal cell (`column`, `row`) of a canvas
+    /// drawn in `area`, or `None` if the cell is outside of it
+    fn pixel_to_coord(&self, column: u16, row: u16, area: Rect) -> Option<(f64, f64)> {
+        if !area.contains(ratatui::layout::Position::new(column, row)) {
+            return None;
+        }
+        let x = (f64::from(column - area.x) + 0.5) / f64::from(area.width);
+        let y = (f64::from(row - area.y) + 0.5) / f64::from(area.height);
+        let ([left, right], [bottom, top]) = self.canvas_bounds();
+        self.projection.coordinate(
+            left + x * (right - left),
+            top - y * (top - bottom),
+            self.center(),
+        )
+    }
+
+    /// Coordinate `columns` and `rows` cells away from the top left corner of a canvas
+    /// drawn in `area`, also past its edges
+    fn cell_to_coord(&self, columns: f64, rows: f64, area: Rect) -> (f64, f64) {
+        let x = columns / f64::from(area.width.max(1));
+        let y = rows / f64::from(area.height.max(1));
+        (
+            self.min_x + x * (self.max_x - self.min_x),
+            self.latitude_at(y),
+        )
+    }
+
+    /// Terminal cell of a canvas drawn in `area` holding (`lon`, `lat`), or `None`
+    /// if it is out of view. The inverse of `pixel_to_coord`
+    fn coord_to_pixel(&self, lon: f64, lat: f64, area: Rect) -> Option<(u16, u16)> {
+        let (x, y) = self.projection.point(lon, lat, self.center())?;
+        let ([left, right], [bottom, top]) = self.canvas_bounds();
+        let (x, y) = ((x - left) / (right - left), (top - y) / (top - bottom));
+        if !(0. ..1.).contains(&x) || !(0. ..1.).contains(&y) {
+            return None;
+        }
+        Some((
+            area.x + (x * f64::from(area.width)) as u16,
+            area.y + (y * f64::from(area.height)) as u16,
+        ))
+    }
+
+    /// Horizontal and vertical bounds of the canvas. The globe is drawn around the
+    /// canvas origin, so its view is shifted there
+    fn canvas_bounds(&self) -> ([f64; 2], [f64; 2]) {
+        match self.projection {
+            Projection::Orthographic => {
+                let (x, y) = self.center();
+                (
+                    [self.min_x - x, self.max_x - x],
+                    [self.min_y - y, self.max_y - y],
+                )
+            }
+            _ => ([self.min_x, self.max_x], self.y_bounds()),
+        }
+    }
+
+    /// Vertical bounds of the canvas, the latitude bounds projected
+    fn y_bounds(&self) -> [f64; 2] {
+        let limit = self.projection.max_latitude();
+        [self.min_y, self.max_y].map(|lat| self.projection.y(lat.clamp(-limit, limit)))
+    }
+
+    /// Latitude at `fraction` of the canvas height, from the top edge
+    fn latitude_at(&self, fraction: f64) -> f64 {
+        let [bottom, top] = self.y_bounds();
+        self.projection.latitude(top - fraction * (top - bottom))
+    }
+
+    /// [min_x, min_y, max_x, max_y]
+    fn bounds(&self) -> [f64; 4] {
+        [self.min_x, self.min_y, self.max_x, self.max_y]
     }
+
+    /// Moves the view to be centered on (x, y), keeping the zoom. Near the poles
+    /// the view stops at the edge of the world, off center
+    fn center_on(&mut self, x: f64, y: f64) {
+        let (center_x, center_y) = self.center();
+        let (dx, dy) = (x - center_x, y - center_y);
+        self.min_x += dx;
+        self.max_x += dx;
+        self.min_y += dy;
+        self.max_y += dy;
+        self.clamp();
+    }
+
+    fn center(&self) -> (f64, f64) {
+        (
+            (self.min_x + self.max_x) / 2.,
+            (self.min_y + self.max_y) / 2.,
+        )
+    }
+
+    /// Zooms in `z` steps, or out if negative. Returns `false` without touching the
+    /// view when that would shrink it below `MIN_SPAN` or grow it past
+    /// `MAX_SPAN`
+    fn zoom(&mut self, z: i32) -> bool {
+        let step = f64::from(z) * ZOOM_STEP_SIZE;
+        let width = self.max_x - self.min_x - 2. * step;
+        let height = self.max_y - self.min_y - step;
+        // some slack so rounding doesn't keep the view from zooming back out
+        let too_wide = width > MAX_SPAN.0 + 1e-9 || height > MAX_SPAN.1 + 1e-9;
+        if width < MIN_SPAN || height < MIN_SPAN || (z < 0 && too_wide) {
+            return false;
+        }
+        self.zoom_level += step;
+        self.min_x += step;
+        self.min_y += step / 2.;
+        self.max_x -= step;
+        self.max_y -= step / 2.;
+        self.clamp();
+        true
+    }
+
+    /// Scales the view by `factor`, below 1 zooming in, keeping (`focus_x`,
+    /// `focus_y`) at the same place on screen. Same limits as `zoom`, but a step
+    /// that would cross them goes as far as they allow instead of being skipped
+    fn zoom_at(&mut self, factor: f64, focus_x: f64, focus_y: f64) -> bool {
+        let (width, height) = (self.max_x - self.min_x, self.max_y - self.min_y);
+        let factor = if factor > 1. {
+            factor
+                .min((MAX_SPAN.0 / width).min(MAX_SPAN.1 / height))
+                .max(1.)
+        } else {
+            factor
+                .max((MIN_SPAN / width).max(MIN_SPAN / height))
+                .min(1.)
+        };
+        if factor == 1. {
+            return false;
+        }
+        self.zoom_level += width * (1. - factor) / 2.;
+        self.min_x = focus_x - (focus_x - self.min_x) * factor;
+        self.max_x = focus_x + (self.max_x - focus_x) * factor;
+        self.min_y = focus_y - (focus_y - self.min_y) * factor;
+        self.max_y = focus_y + (self.max_y - focus_y) * factor;
+        self.clamp();
+        true
+    }
+
+    /// Shifts the view back inside -90..90 without resizing it, and brings its
+    /// center back to -180..180 since the map wraps around in longitude. A view
+    /// larger than the world on an axis is centered on it instead
+    fn clamp(&mut self) {
+        let (center_x, _) = self.center();
+        if self.max_x - self.min_x > 360. {
+            (self.min_x, self.max_x) = clamp_range(self.min_x, self.max_x, 180.);
+        } else if center_x.abs() > 180. {
+            let shift = geo::relative_longitude(center_x, 0.) - center_x;
+            self.min_x += shift;
+            self.max_x += shift;
+        }
+        let limit = self.projection.max_latitude();
+        if self.projection == Projection::Orthographic {
+            // the globe turns to face any latitude, up to the poles
+            let (_, center_y) = self.center();
+            let shift = center_y.clamp(-limit, limit) - center_y;
+            self.min_y += shift;
+            self.max_y += shift;
+        } else {
+            (self.min_y, self.max_y) = clamp_range(self.min_y, self.max_y, limit);
+        }
+    }
+
+    /// Whether the view reaches past ±180, where the map has to be drawn wrapped.
+    /// The globe never needs it
+    fn crosses_antimeridian(&self) -> bool {
+        self.projection != Projection::Orthographic && (self.min_x < -180. || self.max_x > 180.)
+    }
+}
+
+/// `min`..`max` moved inside -`limit`..`limit`, or centered on 0 if it's wider
+fn clamp_range(min: f64, max: f64, limit: f64) -> (f64, f64) {
+    let span = max - min;
+    if span > 2. * limit {
+        (-span / 2., span / 2.)
+    } else if min < -limit {
+        (-limit, -limit + span)
+    } else if max > limit {
+        (limit - span, limit)
+    } else {
+        (min, max)
+    }
+}
+
+/// Multipliers applied to mouse drag panning on each axis, for terminals where
+/// the cell aspect ratio makes one axis feel faster than the other
+#[derive(Debug)]
+struct DragSensitivity {
+    horizontal: f64,
+    vertical: f64,
+}
+
+impl Default for DragSensitivity {
+    fn default() -> Self {
+        Self {
+            horizontal: 1.,
+            vertical: 1.,
+        }
+    }
+}
+
+/// Developer overlay showing how terminal cells map to coordinates
+#[derive(Debug, Clone, Default, Copy, Eq, PartialEq, Hash)]
+enum DebugOverlay {
+    #[default]
+    Off,
+    /// corner coordinates and degrees per cell
+    Labels,
+    /// labels plus alternate cells shaded to make the grid visible
+    Checkerboard,
 }
 
-#[derive(Debug, Default)]
+impl DebugOverlay {
+    fn next(self) -> Self {
+        match self {
+            Self::Off => Self::Labels,
+            Self::Labels => Self::Checkerboard,
+            Self::Checkerboard => Self::Off,
+        }
+    }
+}
+
+#[derive(Debug)]
 pub struct App {
     exit: bool,
     viewport: Viewport,
     /// last seen mouse clicking position
     last_mouse_drag_position: Option<(u16, u16)>,
+    /// cell where the left button went down, forgotten once the pointer drags away
+    /// so releasing it only recenters the map after a plain click
+    pressed_at: Option<(u16, u16)>,
+    drag_sensitivity: DragSensitivity,
+    coordinate_format: CoordinateFormat,
+    /// decimals of the coordinates in readouts, labels and commands
+    precision: usize,
+    /// longitude displayed as 0, the data itself stays relative to Greenwich
+    prime_meridian: f64,
+    /// layers drawn on the canvas, in order
+    layers: Vec<MapLayer>,
+    /// earlier render drawn dimmed beneath the live map, to compare two views
+    snapshot: Option<Buffer>,
+    /// capture the next rendered frame as the snapshot
+    capture_snapshot: bool,
+    /// the snapshot captured before `snapshot`
+    previous_snapshot: Option<Buffer>,
+    /// show the cells that changed between the last two snapshots instead of the map
+    show_snapshot_diff: bool,
+    debug_overlay: DebugOverlay,
+    reset_target: ResetTarget,
+    /// resolution picked by hand, used instead of the one matching the zoom
+    resolution_override: Option<WorldResolution>,
+    /// by default overlays are hidden during a drag so panning stays smooth
+    keep_overlays_while_dragging: bool,
+    /// area of the canvas in the last render, to map mouse positions to coordinates
+    canvas_area: Cell<Rect>,
+    /// clicking pins the coordinate readout instead of panning
+    inspect_mode: bool,
+    /// coordinate shown in the readout instead of the center of the view
+    pinned_coordinate: Option<(f64, f64)>,
+    /// index and position of the coastline point under the cursor in inspect mode
+    nearest_coastline: Option<(usize, (f64, f64))>,
+    /// fixed reference marker, kept apart from the loaded data
+    home: Option<(f64, f64)>,
+    show_home: bool,
+    /// layers skipped while the view is moving
+    heavy_layers: Vec<Layer>,
+    /// when the viewport last changed
+    last_interaction: Option<Instant>,
+    open_command: Option<Vec<String>>,
+    snap_drag: bool,
+    /// fraction of a cell dragged but not yet applied when snapping to cells
+    drag_remainder: (f64, f64),
+    max_points_per_cell: Option<u8>,
+    /// coordinate ticks along the top and left edges of the canvas
+    show_ruler: bool,
+    coastline_thickness: u8,
+    /// keep the longitude fixed while panning
+    lock_x: bool,
+    /// keep the latitude fixed while panning
+    lock_y: bool,
+    /// shortest time between two redraws
+    frame_interval: Duration,
+    /// `None` leaves Ctrl+C as the only way to quit
+    quit_key: Option<KeyBinding>,
+    /// rings at round distances around the center of the view
+    show_range_rings: bool,
+    /// meridians and parallels beneath the map
+    show_graticule: bool,
+    /// widest spacing of the graticule, it gets finer when zooming in
+    graticule_step: f64,
+    /// coordinates of the corners and edge midpoints of the view
+    show_extent_labels: bool,
+    /// degrees the simplified coastline may stray from the full one
+    simplify_tolerance: f64,
+    /// coastline simplified the first time it is shown, a lighter alternative to
+    /// the full data, with the indices where a new path starts
+    simplified: OnceCell<Points>,
+    /// draw `simplified` instead of the full coastline
+    use_simplified: bool,
+    /// draw the coastline as connected lines rather than dots
+    coastline_lines: bool,
+    /// only draw the map inside a circle inscribed in the canvas
+    circular_clip: bool,
+    /// dim the map towards the edges of the canvas
+    show_vignette: bool,
+    /// the terminal reports key releases, so holding a zoom key zooms steadily
+    /// instead of relying on key repeat
+    continuous_zoom: bool,
+    /// direction of the zoom key being held and when it last zoomed
+    held_zoom: Option<(i32, Instant)>,
+    /// labelled markers given on the command line or dropped with a right click
+    marks: Vec<Mark>,
+    /// when the markers started blinking, `None` keeps them steady
+    blink_start: Option<Instant>,
+    /// format of the next bounding box shown and copied
+    bbox_format: BboxFormat,
+    /// text to hand to the terminal clipboard once the next frame is drawn
+    clipboard: Option<String>,
+    /// turns off every animation, checked by each of them
+    reduce_motion: bool,
+    /// outcome of the last command, shown until the next key press
+    status_message: Option<String>,
+    /// query typed after `/`, while it is `Some` keys edit it instead of moving
+    /// the map
+    search: Option<String>,
+    /// index in `marks` of the match the view last jumped to
+    search_match: Option<usize>,
+    /// last terminal cell the mouse was seen over
+    cursor: Option<(u16, u16)>,
+}
+
+impl Default for App {
+    fn default() -> Self {
+        Self {
+            exit: false,
+            viewport: Viewport::default(),
+            last_mouse_drag_position: None,
+            pressed_at: None,
+            drag_sensitivity: DragSensitivity::default(),
+            coordinate_format: CoordinateFormat::default(),
+            precision: DEFAULT_PRECISION,
+            prime_meridian: 0.,
+            layers: vec![MapLayer::world(ratatui::style::Color::Blue)],
+            snapshot: None,
+            capture_snapshot: false,
+            previous_snapshot: None,
+            show_snapshot_diff: false,
+            debug_overlay: DebugOverlay::default(),
+            reset_target: ResetTarget::default(),
+            resolution_override: None,
+            keep_overlays_while_dragging: false,
+            canvas_area: Cell::default(),
+            inspect_mode: false,
+            pinned_coordinate: None,
+            nearest_coastline: None,
+            home: None,
+            show_home: false,
+            heavy_layers: Vec::new(),
+            last_interaction: None,
+            open_command: None,
+            snap_drag: false,
+            drag_remainder: (0., 0.),
+            max_points_per_cell: None,
+            show_ruler: false,
+            coastline_thickness: 1,
+            lock_x: false,
+            lock_y: false,
+            frame_interval: Duration::from_secs(1) / DEFAULT_MAX_FPS,
+            quit_key: Some(KeyBinding::default()),
+            show_range_rings: false,
+            show_graticule: false,
+            graticule_step: GRATICULE_STEP,
+            show_extent_labels: false,
+            simplify_tolerance: SIMPLIFY_TOLERANCE,
+            simplified: OnceCell::new(),
+            use_simplified: false,
+            coastline_lines: false,
+            circular_clip: false,
+            show_vignette: false,
+            continuous_zoom: false,
+            held_zoom: None,
+            marks: Vec::new(),
+            blink_start: None,
+            bbox_format: BboxFormat::default(),
+            clipboard: None,
+            reduce_motion: false,
+            status_message: None,
+            search: None,
+            search_match: None,
+            cursor: None,
+        }
+    }
 }
 
 impl App {
+    pub fn new(args: cli::Args) -> Result<Self> {
+        let mut app = Self::default();
+        if let Some((horizontal, vertical)) = args.drag_sensitivity {
+            app.drag_sensitivity = DragSensitivity {
+                horizontal,
+                vertical,
+            };
+        }
+        if args.debug {
+            app.debug_overlay = DebugOverlay::Labels;
+        }
+        app.reset_target = args.reset_target.unwrap_or_default();
+        app.keep_overlays_while_dragging = args.keep_overlays_while_dragging;
+        app.home = args.home;
+        app.show_home = args.home.is_some();
+        app.heavy_layers = args.heavy_layers;
+        app.open_command = args.open_command;
+        app.snap_drag = args.snap_drag;
+        app.coordinate_format = args.coordinate_format.unwrap_or_default();
+        app.precision = args.precision.unwrap_or(app.precision);
+        app.prime_meridian = args.prime_meridian.unwrap_or_default();
+        app.marks = args.marks;
+        for path in &args.mark_files {
+            app.marks.extend(loader::load_marks(path)?);
+        }
+        app.blink_start = args.blink_marks.then(Instant::now);
+        app.reduce_motion = args.reduce_motion;
+        app.graticule_step = args.graticule_step.unwrap_or(app.graticule_step);
+        app.simplify_tolerance = args.simplify_tolerance.unwrap_or(SIMPLIFY_TOLERANCE);
+        app.coastline_thickness = args.thickness.unwrap_or(app.coastline_thickness);
+        app.max_points_per_cell = args.max_points_per_cell.filter(|max| *max > 0);
+        if let Some(max_fps) = args.max_fps {
+            app.frame_interval = Duration::from_secs(1) / max_fps;
+        }
+        app.quit_key = args.quit_key.unwrap_or(app.quit_key);
+        if let Some([min_x, min_y, max_x, max_y]) =
+            state::view_path().and_then(|path| state::load_view(&path))
+        {
+            app.set_viewport(Viewport::from_bounds(min_x, min_y, max_x, max_y));
+        }
+        let mut projection = args.projection;
+        // the manifest replaces the default world layer
+        if let Some(path) = args.manifest {
+            let manifest = loader::load_manifest(&path)?;
+            if let Some(view) = manifest.view {
+                let [min_x, min_y, max_x, max_y] = view.bounds;
+                app.viewport = Viewport::from_bounds(min_x, min_y, max_x, max_y);
+                projection = projection.or(view.projection);
+            }
+            let dir = path.parent().unwrap_or(Path::new("."));
+            app.layers = manifest
+                .into_layers(dir)
+                .wrap_err_with(|| format!("failed to load {}", path.display()))?;
+            for layer in &app.layers {
+                eprintln!("loaded layer {}", layer.name);
+            }
+        }
+        if let Some(path) = args.data {
+            let points = loader::load_points(&path)?;
+            let layer = MapLayer {
+                name: path.display().to_string(),
+                path: Some(path),
+                data: LayerData::Points(points),
+                ..MapLayer::world(ratatui::style::Color::Blue)
+            };
+            // the data stands in for the baked coastline
+            match app
+                .layers
+                .iter_mut()
+                .find(|layer| layer.data == LayerData::World)
+            {
+                Some(world) => {
+                    *world = MapLayer {
+                        color: world.color,
+                        visible: world.visible,
+                        ..layer
+                    }
+                }
+                None => app.layers.push(layer),
+            }
+        }
+        if let Some(path) = args.wkt {
+            let wkt = loader::load_wkt(&path)?;
+            for tag in wkt.skipped {
+                eprintln!("skipping unsupported WKT geometry {tag}");
+            }
+            app.layers.push(MapLayer {
+                name: path.display().to_string(),
+                path: Some(path),
+                data: LayerData::Lines(wkt.polylines, LineFormat::Wkt),
+                color: ratatui::style::Color::Yellow,
+                visible: true,
+            });
+        }
+        if let Some(path) = args.geojson {
+            let polylines = loader::load_geojson(&path)?;
+            app.layers.push(MapLayer {
+                name: path.display().to_string(),
+                path: Some(path),
+                data: LayerData::Lines(polylines, LineFormat::GeoJson),
+                color: ratatui::style::Color::Yellow,
+                visible: true,
+            });
+        }
+        // over both the restored view and the manifest's
+        if let Some([min_x, min_y, max_x, max_y]) = args.bbox {
+            app.set_viewport(Viewport::from_bounds(min_x, min_y, max_x, max_y));
+        }
+        // after the manifest, which may have set the view
+        app.set_projection(projection.unwrap_or_default());
+        Ok(app)
+    }
+
     pub fn run(&mut self, terminal: &mut tui::Tui) -> Result<()> {
-        while !self.exit {
-            terminal.draw(|frame| self.render_frame(frame))?;
+        while !self.exited() {
+            if self.capture_snapshot {
+                // the captured frame should only hold the live map
+                self.previous_snapshot = self.snapshot.take();
+            }
+            let frame = terminal.draw(|frame| self.render_frame(frame))?;
+            let drawn_at = Instant::now();
+            if let Some(text) = self.clipboard.take() {
+                tui::copy_to_clipboard(&text)?;
+            }
+            if self.capture_snapshot {
+                self.snapshot = Some(frame.buffer.clone());
+                self.capture_snapshot = false;
+            }
             self.handle_events().wrap_err("handle event failed")?;
+            // hold the next frame back to respect the frame rate cap, but keep
+            // handling the input that arrives meanwhile
+            while let Some(wait) = self.frame_interval.checked_sub(drawn_at.elapsed()) {
+                if self.exited() || !event::poll(wait)? {
+                    break;
+                }
+                self.handle_event().wrap_err("handle event failed")?;
+            }
         }
         Ok(())
     }
@@ -94,54 +703,353 @@ impl App {
     }
 
     fn handle_events(&mut self) -> Result<()> {
-        match event::read()? {
-            Event::Key(key_event) if key_event.kind == KeyEventKind::Press => self
+        if let Some((direction, last_step)) = self.held_zoom {
+            if !event::poll(ZOOM_TICK.saturating_sub(last_step.elapsed()))? {
+                // stop ticking once the zoom limit is reached, nothing changes anymore
+                self.held_zoom = self
+                    .viewport
+                    .zoom(direction)
+                    .then(|| (direction, Instant::now()));
+                self.last_interaction = Some(Instant::now());
+                return Ok(());
+            }
+        } else if let Some(timeout) = [self.time_until_settled(), self.time_until_blink()]
+            .into_iter()
+            .flatten()
+            .min()
+        {
+            if !event::poll(timeout)? {
+                // the view settled or the markers blinked, return to draw again
+                return Ok(());
+            }
+        }
+        self.handle_event()
+    }
+
+    /// Reads and handles a single event, blocking until one is available
+    fn handle_event(&mut self) -> Result<()> {
+        let viewport = self.viewport.clone();
+        let result = match event::read()? {
+            Event::Key(key_event) if key_event.kind == KeyEventKind::Release => {
+                self.handle_key_release(key_event);
+                Ok(())
+            }
+            // held zoom keys are already stepped on every tick
+            Event::Key(key_event)
+                if key_event.kind == KeyEventKind::Repeat && self.held_zoom.is_some() =>
+            {
+                Ok(())
+            }
+            Event::Key(key_event) => self
                 .handle_key_event(key_event)
                 .wrap_err_with(|| format!("handling key event failed: \n{key_event:#?}")),
             Event::Mouse(mouse_event) => self
                 .handle_mouse_event(mouse_event)
                 .wrap_err_with(|| format!("handling mouse event failed: \n{mouse_event:#?}")),
+            // no more mouse moves arrive, the last position would go stale
+            Event::FocusLost => {
+                self.cursor = None;
+                Ok(())
+            }
 
             _ => Ok(()),
+        };
+        if self.viewport != viewport {
+            self.last_interaction = Some(Instant::now());
         }
+        result
+    }
+
+    fn time_until_settled(&self) -> Option<Duration> {
+        self.last_interaction
+            .and_then(|instant| SETTLE_TIME.checked_sub(instant.elapsed()))
+    }
+
+    /// When the markers started blinking, if they are blinking at all
+    fn blinking_since(&self) -> Option<Instant> {
+        self.blink_start
+            .filter(|_| !self.reduce_motion && !self.marks.is_empty())
+    }
+
+    fn time_until_blink(&self) -> Option<Duration> {
+        let start = self.blinking_since()?;
+        let interval = BLINK_INTERVAL.as_millis();
+        let elapsed = start.elapsed().as_millis() % interval;
+        Some(Duration::from_millis((interval - elapsed) as u64))
+    }
+
+    fn marks_visible(&self) -> bool {
+        self.blinking_since().is_none_or(|start| {
+            (start.elapsed().as_millis() / BLINK_INTERVAL.as_millis()).is_multiple_of(2)
+        })
+    }
+
+    /// Whether `layer` should be skipped because it is heavy and the view is moving
+    fn layer_paused(&self, layer: Layer) -> bool {
+        self.heavy_layers.contains(&layer) && self.time_until_settled().is_some()
     }
 
     fn handle_key_event(&mut self, key_event: KeyEvent) -> Result<()> {
+        // raw mode swallows the interrupt, so Ctrl+C always quits
+        let interrupt = key_event.code == KeyCode::Char('c')
+            && key_event.modifiers.contains(KeyModifiers::CONTROL);
+        if interrupt {
+            self.exit();
+            return Ok(());
+        }
+        if self.search.is_some() {
+            self.handle_search_key(key_event);
+            return Ok(());
+        }
+        if self.quit_key.is_some_and(|key| key.matches(&key_event)) {
+            self.exit();
+            return Ok(());
+        }
+        self.status_message = None;
         match key_event.code {
-            KeyCode::Char('q') => self.exit(),
-            KeyCode::Up => self.increment_zoom()?,
-            KeyCode::Down => self.decrement_zoom()?,
-            KeyCode::Char('w') => self.pan_up()?,
-            KeyCode::Char('a') => self.pan_left()?,
-            KeyCode::Char('s') => self.pan_down()?,
-            KeyCode::Char('d') => self.pan_right()?,
+            // shift turns the arrows into a fine pan of one cell
+            KeyCode::Up if key_event.modifiers.contains(KeyModifiers::SHIFT) => {
+                self.pan_cells(0., -1.)
+            }
+            KeyCode::Down if key_event.modifiers.contains(KeyModifiers::SHIFT) => {
+                self.pan_cells(0., 1.)
+            }
+            KeyCode::Left if key_event.modifiers.contains(KeyModifiers::SHIFT) => {
+                self.pan_cells(-1., 0.)
+            }
+            KeyCode::Right if key_event.modifiers.contains(KeyModifiers::SHIFT) => {
+                self.pan_cells(1., 0.)
+            }
+            KeyCode::Up | KeyCode::Char('+') => self.start_zoom(1),
+            KeyCode::Down | KeyCode::Char('-') => self.start_zoom(-1),
+            KeyCode::Char('w' | 'k') => self.pan_up()?,
+            KeyCode::Char('a' | 'h') => self.pan_left()?,
+            KeyCode::Char('s' | 'j') => self.pan_down()?,
+            KeyCode::Char('d' | 'l') => self.pan_right()?,
+            // the corners of the numpad, which send these with num lock off
+            KeyCode::Home => self.pan_diagonal(-1., 1.)?,
+            KeyCode::PageUp => self.pan_diagonal(1., 1.)?,
+            KeyCode::End => self.pan_diagonal(-1., -1.)?,
+            KeyCode::PageDown => self.pan_diagonal(1., -1.)?,
+            KeyCode::Char('f') => self.coordinate_format = self.coordinate_format.next(),
+            KeyCode::Char('c') => self.capture_snapshot = true,
+            KeyCode::Char('x') => {
+                self.snapshot = None;
+                self.previous_snapshot = None;
+            }
+            KeyCode::F(11) => self.show_snapshot_diff = !self.show_snapshot_diff,
+            KeyCode::F(12) => self.debug_overlay = self.debug_overlay.next(),
+            KeyCode::Char('[') => self.resolution_override = Some(self.resolution().coarser()),
+            KeyCode::Char(']') => self.resolution_override = Some(self.resolution().finer()),
+            KeyCode::Char('\\') => self.resolution_override = None,
+            KeyCode::Char('m') => self.resolution_override = Some(self.resolution().next()),
+            KeyCode::Char('r') => self.reset_viewport(),
+            KeyCode::Char('i') => {
+                self.inspect_mode = !self.inspect_mode;
+                self.nearest_coastline = None;
+            }
+            KeyCode::Esc => self.pinned_coordinate = None,
+            KeyCode::Char('H') => self.show_home = !self.show_home,
+            KeyCode::Char('o') => self.open_externally(),
+            KeyCode::Char('u') => self.show_ruler = !self.show_ruler,
+            KeyCode::Char('X') => self.lock_x = !self.lock_x,
+            KeyCode::Char('Y') => self.lock_y = !self.lock_y,
+            KeyCode::Char('R') => self.show_range_rings = !self.show_range_rings,
+            KeyCode::Char('g') => self.show_graticule = !self.show_graticule,
+            KeyCode::Char('b') => self.show_extent_labels = !self.show_extent_labels,
+            KeyCode::Char('M') => self.export_manifest(Path::new(MANIFEST_EXPORT_PATH)),
+            KeyCode::Char('P') => self.fit_pin_and_home(),
+            KeyCode::Char('v') => self.show_vignette = !self.show_vignette,
+            KeyCode::Char('O') => self.circular_clip = !self.circular_clip,
+            KeyCode::Char('S') => self.use_simplified = !self.use_simplified,
+            KeyCode::Char('L') => self.coastline_lines = !self.coastline_lines,
+            KeyCode::Char('p') => self.set_projection(self.viewport.projection.next()),
+            KeyCode::Char('C') => self.center_on_centroid(),
+            KeyCode::Char('B') => {
+                let bbox = self
+                    .bbox_format
+                    .format(self.viewport.bounds(), self.precision);
+                self.status_message = Some(format!("{bbox} copied"));
+                self.clipboard = Some(bbox);
+                self.bbox_format = self.bbox_format.next();
+            }
+            KeyCode::Char('/') => {
+                self.search = Some(String::new());
+                self.search_match = None;
+            }
             _ => {}
         }
         Ok(())
     }
 
+    /// Edits the search query, Enter jumps to the next matching mark and Esc
+    /// leaves the search
+    fn handle_search_key(&mut self, key_event: KeyEvent) {
+        let Some(query) = &mut self.search else {
+            return;
+        };
+        match key_event.code {
+            KeyCode::Char(c) => {
+                query.push(c);
+                self.search_match = None;
+            }
+            KeyCode::Backspace => {
+                query.pop();
+                self.search_match = None;
+            }
+            KeyCode::Enter => self.next_search_match(),
+            KeyCode::Esc => {
+                self.search = None;
+                self.search_match = None;
+            }
+            _ => {}
+        }
+    }
+
+    /// Whether `mark` is shown, while searching only the matching marks are
+    fn matches_search(&self, mark: &Mark) -> bool {
+        let Some(query) = self.search.as_deref().filter(|query| !query.is_empty()) else {
+            return true;
+        };
+        mark.label
+            .as_deref()
+            .is_some_and(|label| label.to_lowercase().contains(&query.to_lowercase()))
+    }
+
+    /// Centers the view on the first match after the current one, wrapping around
+    fn next_search_match(&mut self) {
+        let start = self.search_match.map_or(0, |index| index + 1);
+        let found = (0..self.marks.len())
+            .map(|offset| (start + offset) % self.marks.len())
+            .find(|&index| self.matches_search(&self.marks[index]));
+        match found {
+            Some(index) => {
+                let (lon, lat) = (self.marks[index].lon, self.marks[index].lat);
+                self.viewport.center_on(lon, lat);
+                self.search_match = Some(index);
+            }
+            None => self.search_match = None,
+        }
+    }
+
     fn handle_mouse_event(&mut self, mouse_event: MouseEvent) -> Result<()> {
+        if matches!(
+            mouse_event.kind,
+            MouseEventKind::Moved | MouseEventKind::Drag(_)
+        ) {
+            self.cursor = Some((mouse_event.column, mouse_event.row));
+        }
         match mouse_event.kind {
             MouseEventKind::Drag(MouseButton::Left) => {
-                if let Some((column, row)) = &self.last_mouse_drag_position {
-                    let vertical_delta =
-                        f64::from(i32::from(mouse_event.row).wrapping_sub(i32::from(*row))) * 0.2;
-                    let horizontal_delta =
-                        f64::from(i32::from(mouse_event.column).wrapping_sub(i32::from(*column)))
-                            * 0.2;
-                    self.viewport.max_x -= horizontal_delta;
-                    self.viewport.min_x -= horizontal_delta;
-                    self.viewport.max_y += vertical_delta;
-                    self.viewport.min_y += vertical_delta;
+                // the first drag event already moves from where the button went down
+                let from = self.last_mouse_drag_position.or(self.pressed_at);
+                if self.pressed_at != Some((mouse_event.column, mouse_event.row)) {
+                    self.pressed_at = None;
+                }
+                if let Some((column, row)) = from {
+                    let mut columns =
+                        f64::from(i32::from(mouse_event.column).wrapping_sub(i32::from(column)))
+                            * self.drag_sensitivity.horizontal;
+                    let mut rows =
+                        f64::from(i32::from(mouse_event.row).wrapping_sub(i32::from(row)))
+                            * self.drag_sensitivity.vertical;
+                    if self.snap_drag {
+                        (columns, rows) = self.snap_to_cells(columns, rows);
+                    }
+                    // the point grabbed stays under the cursor, the rows are not
+                    // evenly spaced in latitude in every projection
+                    let area = self.canvas_area.get();
+                    let (column, row) = (
+                        f64::from(column.saturating_sub(area.x)) + 0.5,
+                        f64::from(row.saturating_sub(area.y)) + 0.5,
+                    );
+                    let (grab_x, grab_y) = self.viewport.cell_to_coord(column, row, area);
+                    let (x, y) = self
+                        .viewport
+                        .cell_to_coord(column + columns, row + rows, area);
+                    self.pan_by(grab_x - x, grab_y - y);
                 }
                 self.last_mouse_drag_position = Some((mouse_event.column, mouse_event.row));
             }
-            MouseEventKind::Up(_) => {
+            MouseEventKind::Up(button) => {
                 // Dragging finishes
                 self.last_mouse_drag_position = None;
+                self.drag_remainder = (0., 0.);
+                if let Some((column, row)) = self.pressed_at.take() {
+                    let clicked = self
+                        .viewport
+                        .pixel_to_coord(column, row, self.canvas_area.get());
+                    if let (MouseButton::Left, Some((lon, lat))) = (button, clicked) {
+                        self.viewport.center_on(lon, lat);
+                    }
+                }
+            }
+            MouseEventKind::Down(MouseButton::Middle) => self.reset_viewport(),
+            MouseEventKind::Down(MouseButton::Left) if self.inspect_mode => {
+                if let Some((lon, lat)) = self.viewport.pixel_to_coord(
+                    mouse_event.column,
+                    mouse_event.row,
+                    self.canvas_area.get(),
+                ) {
+                    // kept within -180..180 so it stays put when the view pans back
+                    self.pinned_coordinate = Some((geo::relative_longitude(lon, 0.), lat));
+                }
+            }
+            MouseEventKind::Down(MouseButton::Left) => {
+                self.pressed_at = Some((mouse_event.column, mouse_event.row));
+            }
+            MouseEventKind::Down(MouseButton::Right) => {
+                if let Some((lon, lat)) = self.viewport.pixel_to_coord(
+                    mouse_event.column,
+                    mouse_event.row,
+                    self.canvas_area.get(),
+                ) {
+                    self.marks.push(Mark {
+                        lon: geo::relative_longitude(lon, 0.),
+                        lat,
+                        label: None,
+                    });
+                }
+            }
+            MouseEventKind::Moved if self.inspect_mode => {
+                let area = self.canvas_area.get();
+                let cell_size = (
+                    (self.viewport.max_x - self.viewport.min_x) / f64::from(area.width),
+                    (self.viewport.max_y - self.viewport.min_y) / f64::from(area.height),
+                );
+                self.nearest_coastline = self
+                    .viewport
+                    .pixel_to_coord(mouse_event.column, mouse_event.row, area)
+                    .and_then(|(lon, lat)| {
+                        // the view may have panned a turn or more around the world
+                        let cursor = (geo::relative_longitude(lon, 0.), lat);
+                        let (data, resolution) = self.coastline();
+                        let columns = resolution.map(WorldResolution::columns);
+                        map::nearest_point(data, columns, cursor, cell_size, NEAREST_POINT_CELLS)
+                    });
+            }
+            MouseEventKind::ScrollUp | MouseEventKind::ScrollDown => {
+                let factor = match mouse_event.kind {
+                    MouseEventKind::ScrollUp => SCROLL_ZOOM_FACTOR,
+                    _ => 1. / SCROLL_ZOOM_FACTOR,
+                };
+                // zoom around the point under the cursor, as web maps do. The globe
+                // only grows or shrinks around its center
+                let focus = self
+                    .viewport
+                    .pixel_to_coord(mouse_event.column, mouse_event.row, self.canvas_area.get())
+                    .map(|focus| match self.viewport.projection {
+                        Projection::Orthographic => self.viewport.center(),
+                        _ => focus,
+                    });
+                match focus {
+                    Some((x, y)) => {
+                        self.viewport.zoom_at(factor, x, y);
+                    }
+                    None if factor < 1. => self.increment_zoom()?,
+                    None => self.decrement_zoom()?,
+                }
             }
-            MouseEventKind::ScrollUp => self.increment_zoom()?,
-            MouseEventKind::ScrollDown => self.decrement_zoom()?,
             _ => {}
         }
         Ok(())
@@ -151,6 +1059,226 @@ impl App {
         self.exit = true;
     }
 
+    /// Whole cells to move for a drag of `columns` x `rows`, keeping the fractional
+    /// part for the next drag event
+    fn snap_to_cells(&mut self, columns: f64, rows: f64) -> (f64, f64) {
+        let columns = self.drag_remainder.0 + columns;
+        let rows = self.drag_remainder.1 + rows;
+        self.drag_remainder = (columns.fract(), rows.fract());
+        (columns.trunc(), rows.trunc())
+    }
+
+    /// Whether expensive overlays should be skipped for the current frame
+    fn hide_overlays(&self) -> bool {
+        !self.keep_overlays_while_dragging && self.last_mouse_drag_position.is_some()
+    }
+
+    fn resolution(&self) -> WorldResolution {
+        self.resolution_override
+            .unwrap_or_else(|| WorldResolution::for_span(self.viewport.max_x - self.viewport.min_x))
+    }
+
+    /// The high resolution coastline simplified with `simplify_tolerance`, worked
+    /// out when first needed rather than on every startup
+    fn simplified(&self) -> &Points {
+        self.simplified.get_or_init(|| {
+            let (coordinates, breaks) = map::simplify_paths(
+                WorldResolution::High.data(),
+                WorldResolution::High.path_breaks(),
+                self.simplify_tolerance,
+            );
+            Points {
+                coordinates,
+                breaks,
+            }
+        })
+    }
+
+    /// Points of the coastline being drawn, loaded data stands in for the baked one.
+    /// The baked data comes with the resolution it was picked from
+    fn coastline(&self) -> (&[(f64, f64)], Option<WorldResolution>) {
+        self.layers
+            .iter()
+            .find_map(|layer| match &layer.data {
+                LayerData::Points(points) if layer.visible => {
+                    Some((points.coordinates.as_slice(), None))
+                }
+                _ => None,
+            })
+            .unwrap_or_else(|| {
+                let resolution = self.resolution();
+                (resolution.data(), Some(resolution))
+            })
+    }
+
+    fn reset_viewport(&mut self) {
+        let viewport = match (self.reset_target, self.data_bounds()) {
+            (ResetTarget::Data, Some((min_x, min_y, max_x, max_y))) => {
+                Viewport::fit_bounds(min_x, min_y, max_x, max_y)
+            }
+            _ => Viewport::default(),
+        };
+        self.set_viewport(viewport);
+        // nothing carries on moving the view away from where it was reset to
+        self.last_mouse_drag_position = None;
+        self.drag_remainder = (0., 0.);
+        self.held_zoom = None;
+    }
+
+    /// Frames the pinned coordinate and the home marker together
+    fn fit_pin_and_home(&mut self) {
+        let (Some((mut x1, y1)), Some((mut x2, y2))) = (self.pinned_coordinate, self.home) else {
+            self.status_message = Some("framing needs a pinned coordinate and --home".into());
+            return;
+        };
+        // points more than half the world apart are closer across the antimeridian,
+        // the view wraps around so the western one can move a turn east
+        if (x2 - x1).abs() > 180. {
+            if x1 < x2 {
+                x1 += 360.;
+            } else {
+                x2 += 360.;
+            }
+        }
+        self.set_viewport(Viewport::fit_bounds(
+            x1.min(x2),
+            y1.min(y2),
+            x1.max(x2),
+            y1.max(y2),
+        ));
+    }
+
+    /// Replaces the viewport keeping the projection
+    fn set_viewport(&mut self, viewport: Viewport) {
+        self.viewport = Viewport {
+            projection: self.viewport.projection,
+            ..viewport
+        };
+        self.viewport.clamp();
+    }
+
+    fn set_projection(&mut self, projection: Projection) {
+        self.viewport.projection = projection;
+        self.viewport.clamp();
+    }
+
+    /// Centers the view on the mean position of the topmost visible lines layer
+    fn center_on_centroid(&mut self) {
+        let centroid = self
+            .layers
+            .iter()
+            .rev()
+            .find_map(|layer| match &layer.data {
+                LayerData::Lines(lines, _) if layer.visible => {
+                    geo::centroid(lines.iter().flatten().copied())
+                        .map(|center| (&layer.name, center))
+                }
+                _ => None,
+            });
+        self.status_message = Some(match centroid {
+            Some((name, (x, y))) => {
+                let message = format!("centered on the centroid of {name}");
+                self.viewport.center_on(x, y);
+                message
+            }
+            None => "no loaded lines to center on".to_string(),
+        });
+    }
+
+    /// (min_x, min_y, max_x, max_y) of the loaded data
+    fn data_bounds(&self) -> Option<(f64, f64, f64, f64)> {
+        let points = self.layers.iter().flat_map(|layer| match &layer.data {
+            LayerData::Lines(lines, _) => lines.iter().flatten().collect(),
+            LayerData::Points(points) => points.coordinates.iter().collect(),
+            LayerData::World => Vec::new(),
+        });
+        points.fold(None, |bounds, &(x, y)| {
+            let (min_x, min_y, max_x, max_y) = bounds.unwrap_or((x, y, x, y));
+            Some((min_x.min(x), min_y.min(y), max_x.max(x), max_y.max(y)))
+        })
+    }
+
+    /// Runs the configured open command for the current center, without waiting for
+    /// it. A command that can't be started is reported in the status line
+    fn open_externally(&mut self) {
+        let Some((program, args)) = self
+            .open_command
+            .as_ref()
+            .and_then(|command| command.split_first())
+        else {
+            return;
+        };
+        let (lon, lat) = self.viewport.center();
+        let precision = self.precision;
+        // zoom level of a web map showing roughly the same span
+        let zoom = (360. / (self.viewport.max_x - self.viewport.min_x))
+            .log2()
+            .round()
+            .max(0.);
+        let substitute = |word: &String| {
+            word.replace("{lon}", &format!("{lon:.precision$}"))
+                .replace("{lat}", &format!("{lat:.precision$}"))
+                .replace("{zoom}", &zoom.to_string())
+        };
+        let spawned = std::process::Command::new(substitute(program))
+            .args(args.iter().map(substitute))
+            .stdin(std::process::Stdio::null())
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .spawn();
+        self.status_message = Some(match spawned {
+            Ok(mut child) => {
+                // reaped in the background so it doesn't linger once it exits
+                std::thread::spawn(move || child.wait());
+                format!("opened the view with {program}")
+            }
+            Err(err) => format!("failed to run {program}: {err}"),
+        });
+    }
+
+    /// Writes the layer stack and view to a manifest that `--manifest` can load. An
+    /// existing file at `path` is left as it is
+    fn export_manifest(&mut self, path: &Path) {
+        let manifest = Manifest::new(self.layers(), self.viewport.bounds(), self.projection());
+        let written = toml::to_string(&manifest)
+            .wrap_err("failed to serialize the layers")
+            .and_then(|toml| {
+                let mut file = std::fs::OpenOptions::new()
+                    .write(true)
+                    .create_new(true)
+                    .open(path)?;
+                file.write_all(toml.as_bytes()).wrap_err("write failed")
+            });
+        let path = path.display();
+        self.status_message = Some(match written {
+            Ok(()) => format!("wrote {path}"),
+            Err(err)
+                if err.downcast_ref::<io::Error>().map(io::Error::kind)
+                    == Some(io::ErrorKind::AlreadyExists) =>
+            {
+                format!("{path} already exists, move it away to export again")
+            }
+            Err(err) => format!("failed to export {path}: {err:#}"),
+        });
+    }
+
+    /// Zooms one step, and keeps zooming until the key is released when the
+    /// terminal reports releases
+    fn start_zoom(&mut self, direction: i32) {
+        if self.viewport.zoom(direction) && self.continuous_zoom && !self.reduce_motion {
+            self.held_zoom = Some((direction, Instant::now()));
+        }
+    }
+
+    fn handle_key_release(&mut self, key_event: KeyEvent) {
+        if matches!(
+            key_event.code,
+            KeyCode::Up | KeyCode::Down | KeyCode::Char('+' | '-')
+        ) {
+            self.held_zoom = None;
+        }
+    }
+
     fn increment_zoom(&mut self) -> Result<()> {
         self.viewport.zoom(1);
         Ok(())
@@ -161,116 +1289,1483 @@ impl App {
         Ok(())
     }
 
+    /// Pans by a number of terminal cells of the canvas, rows grow downwards
+    fn pan_cells(&mut self, columns: f64, rows: f64) {
+        let area = self.canvas_area.get();
+        let dx =
+            columns * (self.viewport.max_x - self.viewport.min_x) / f64::from(area.width.max(1));
+        let dy = rows * (self.viewport.max_y - self.viewport.min_y) / f64::from(area.height.max(1));
+        self.pan_by(dx, -dy);
+    }
+
+    /// Moves the viewport by map units, except along the locked axes
+    fn pan_by(&mut self, dx: f64, dy: f64) {
+        if !self.lock_x {
+            self.viewport.min_x += dx;
+            self.viewport.max_x += dx;
+        }
+        if !self.lock_y {
+            self.viewport.min_y += dy;
+            self.viewport.max_y += dy;
+        }
+        self.viewport.clamp();
+    }
+
+    /// Map units moved by one pan step on each axis, a share of what is visible so
+    /// panning feels the same at every zoom
+    fn pan_step(&self) -> (f64, f64) {
+        (
+            (self.viewport.max_x - self.viewport.min_x) * PAN_STEP_FRACTION,
+            (self.viewport.max_y - self.viewport.min_y) * PAN_STEP_FRACTION,
+        )
+    }
+
     fn pan_up(&mut self) -> Result<()> {
-        let step = PAN_STEP_SIZE.div(self.viewport.zoom_level) as f64;
-        self.viewport.max_y += step;
-        self.viewport.min_y += step;
+        let (_, step) = self.pan_step();
+        self.pan_by(0., step);
         Ok(())
     }
     fn pan_left(&mut self) -> Result<()> {
-        let step = PAN_STEP_SIZE.div(self.viewport.zoom_level) as f64;
-        self.viewport.max_x -= step;
-        self.viewport.min_x -= step;
+        let (step, _) = self.pan_step();
+        self.pan_by(-step, 0.);
         Ok(())
     }
     fn pan_down(&mut self) -> Result<()> {
-        let step = PAN_STEP_SIZE.div(self.viewport.zoom_level) as f64;
-        self.viewport.max_y -= step;
-        self.viewport.min_y -= step;
+        let (_, step) = self.pan_step();
+        self.pan_by(0., -step);
         Ok(())
     }
     fn pan_right(&mut self) -> Result<()> {
-        let step = PAN_STEP_SIZE.div(self.viewport.zoom_level) as f64;
-        self.viewport.max_x += step;
-        self.viewport.min_x += step;
+        let (step, _) = self.pan_step();
+        self.pan_by(step, 0.);
+        Ok(())
+    }
+    /// Pans along both axes at once, `x` steps right and `y` steps up
+    fn pan_diagonal(&mut self, x: f64, y: f64) -> Result<()> {
+        let (step_x, step_y) = self.pan_step();
+        self.pan_by(x * step_x, y * step_y);
         Ok(())
     }
 }
 
+/// Read only view of the state, for drawing and for tests driving the app with events
+impl App {
+    pub fn viewport(&self) -> &Viewport {
+        &self.viewport
+    }
+
+    pub fn projection(&self) -> Projection {
+        self.viewport.projection
+    }
+
+    /// Coordinate under the mouse cursor on the last drawn canvas
+    pub fn cursor_coordinate(&self) -> Option<(f64, f64)> {
+        self.cursor.and_then(|(column, row)| {
+            self.viewport
+                .pixel_to_coord(column, row, self.canvas_area.get())
+        })
+    }
+
+    /// The layer stack, in drawing order
+    pub fn layers(&self) -> &[MapLayer] {
+        &self.layers
+    }
+
+    pub fn pinned_coordinate(&self) -> Option<(f64, f64)> {
+        self.pinned_coordinate
+    }
+
+    /// Index and position of the coastline point under the cursor in inspect mode
+    pub fn nearest_coastline(&self) -> Option<(usize, (f64, f64))> {
+        self.nearest_coastline
+    }
+
+    pub fn is_inspecting(&self) -> bool {
+        self.inspect_mode
+    }
+
+    pub fn exited(&self) -> bool {
+        self.exit
+    }
+}
+
 impl Widget for &App {
     fn render(self, area: Rect, buf: &mut Buffer) {
-        let title = Title::from(" Map ".bold());
+        let resolution_mode = match self.resolution_override {
+            Some(_) => "manual",
+            None => "auto",
+        };
+        let title = Title::from(Line::from(vec![
+            " Map ".bold(),
+            format!("- {:?} ({resolution_mode}) ", self.resolution()).into(),
+            if self.use_simplified {
+                "simplified ".into()
+            } else {
+                "".into()
+            },
+            if self.coastline_lines {
+                "lines ".into()
+            } else {
+                "".into()
+            },
+            match self.projection() {
+                Projection::Equirectangular => "".into(),
+                Projection::Mercator => "mercator ".into(),
+                Projection::Orthographic => "globe ".into(),
+            },
+        ]));
         let instructions = Title::from(Line::from(vec![
             " Zoom In ".into(),
-            "<Up>".blue().bold(),
+            "<Up,+>".blue().bold(),
             " Zoom Out ".into(),
-            "<Down>".blue().bold(),
+            "<Down,->".blue().bold(),
             " Pan around ".into(),
-            "<w,a,s,d>".blue().bold(),
+            "<w,a,s,d/h,j,k,l>".blue().bold(),
+            " Reset ".into(),
+            "<r>".blue().bold(),
+            " Coords ".into(),
+            "<f>".blue().bold(),
+            " Snapshot ".into(),
+            "<c,x>".blue().bold(),
+            " Resolution ".into(),
+            "<[,],\\,m>".blue().bold(),
+            " Inspect ".into(),
+            "<i>".blue().bold(),
+            " Search ".into(),
+            "</>".blue().bold(),
             " Quit ".into(),
-            "<Q> ".blue().bold(),
+            match self.quit_key {
+                Some(key) => format!("<{key}> ").blue().bold(),
+                None => "<Ctrl+C> ".blue().bold(),
+            },
         ]));
+        let status = match self.pinned_coordinate() {
+            Some((lon, lat)) => format!(
+                " pinned {} ",
+                self.coordinate_format
+                    .format(lon, lat, self.precision, self.prime_meridian)
+            ),
+            None => {
+                let (lon, lat) = self.viewport.center();
+                format!(
+                    " {} ",
+                    self.coordinate_format
+                        .format(lon, lat, self.precision, self.prime_meridian)
+                )
+            }
+        };
+        let mut status_line = Line::default();
+        if self.is_inspecting() {
+            status_line.push_span(" INSPECT".yellow().bold());
+        }
+        if self.lock_x {
+            status_line.push_span(" LOCK LON".yellow().bold());
+        }
+        if self.lock_y {
+            status_line.push_span(" LOCK LAT".yellow().bold());
+        }
+        if self.show_snapshot_diff {
+            status_line.push_span(" DIFF".yellow().bold());
+        }
+        status_line.push_span(status);
+        if let Some((index, (lon, lat))) = self.nearest_coastline() {
+            status_line.push_span(format!("coastline #{index} {lon}, {lat} ").magenta());
+        }
+        if let Some(message) = &self.status_message {
+            status_line.push_span(format!("{message} ").yellow());
+        }
+        if let Some(query) = &self.search {
+            let matches = self.marks.iter().filter(|mark| self.matches_search(mark));
+            status_line.push_span(format!("/{query}").bold());
+            status_line.push_span(format!(" ({} matches) ", matches.count()).dark_gray());
+        }
+        let status = Title::from(status_line);
         let block = Block::bordered()
             .title(title.alignment(Alignment::Center))
+            .title(status.alignment(Alignment::Left))
             .title(
                 instructions
                     .alignment(Alignment::Center)
                     .position(Position::Bottom),
             )
             .border_set(border::THICK);
+        let canvas_area = block.inner(area);
+        self.canvas_area.set(canvas_area);
+        let block = block.title(
+            Title::from(self.cursor_readout())
+                .alignment(Alignment::Left)
+                .position(Position::Bottom),
+        );
 
+        let drawn = Cell::new(0);
+        let (x_bounds, y_bounds) = self.viewport.canvas_bounds();
         let canvas = Canvas::default()
             .block(block)
-            .x_bounds([self.viewport.min_x, self.viewport.max_x])
-            .y_bounds([self.viewport.min_y, self.viewport.max_y])
+            .x_bounds(x_bounds)
+            .y_bounds(y_bounds)
             .paint(|ctx| {
-                ctx.draw(&WorldMap {
-                    resolution: map::WorldResolution::High,
-                    color: ratatui::style::Color::Blue,
-                });
+                let projection = self.projection();
+                let center = self.viewport.center();
+                let [bottom, top] = y_bounds;
+                if self.show_graticule {
+                    self.draw_graticule(ctx);
+                    // keep the map on top of the grid
+                    ctx.layer();
+                }
+                for layer in self.layers().iter().filter(|layer| layer.visible) {
+                    match &layer.data {
+                        LayerData::World | LayerData::Points(_)
+                            if !self.layer_paused(Layer::Coastline) =>
+                        {
+                            // loaded and simplified coordinates are borrowed, the baked
+                            // dataset is picked by resolution
+                            let coastline = match &layer.data {
+                                LayerData::Points(points) => WorldMap {
+                                    path_breaks: &points.breaks,
+                                    ..WorldMap::from_slice(&points.coordinates, layer.color)
+                                },
+                                _ if self.use_simplified => {
+                                    let simplified = self.simplified();
+                                    WorldMap {
+                                        path_breaks: &simplified.breaks,
+                                        ..WorldMap::from_slice(&simplified.coordinates, layer.color)
+                                    }
+                                }
+                                _ => WorldMap {
+                                    resolution: self.resolution(),
+                                    color: layer.color,
+                                    ..Default::default()
+                                },
+                            };
+                            ctx.draw(&WorldMap {
+                                max_points_per_cell: self.max_points_per_cell,
+                                drawn: Some(&drawn),
+                                thickness: self.coastline_thickness,
+                                // braille cells hold 2x4 grid points
+                                grid_step: (
+                                    (self.viewport.max_x - self.viewport.min_x)
+                                        / f64::from(canvas_area.width * 2),
+                                    (top - bottom) / f64::from(canvas_area.height * 4),
+                                ),
+                                wrap: self.viewport.crosses_antimeridian(),
+                                line_mode: self.coastline_lines,
+                                projection,
+                                center,
+                                // the globe shows more than the bounds
+                                bounds: (projection != Projection::Orthographic)
+                                    .then(|| self.viewport.bounds()),
+                                ..coastline
+                            })
+                        }
+                        LayerData::Lines(lines, _)
+                            if !self.hide_overlays() && !self.layer_paused(Layer::Lines) =>
+                        {
+                            ctx.draw(&map::Polylines {
+                                lines,
+                                color: layer.color,
+                                projection,
+                                wrap: self.viewport.crosses_antimeridian(),
+                                center,
+                            })
+                        }
+                        _ => {}
+                    }
+                }
+                if self.show_range_rings {
+                    self.draw_range_rings(ctx);
+                }
+                // a turn east and west too when the view crosses the antimeridian,
+                // labels falling outside the canvas are left out. Markers beyond the
+                // latitude limit of the projection aren't shown
+                let offsets = map::wrap_offsets(self.viewport.crosses_antimeridian());
+                if let Some((_, (lon, lat))) = self.nearest_coastline() {
+                    for offset in offsets {
+                        if let Some((x, y)) = projection.point(lon + offset, lat, center) {
+                            ctx.print(x, y, "◆".magenta().bold());
+                        }
+                    }
+                }
+                if let Some((lon, lat)) = self.home.filter(|_| self.show_home) {
+                    for offset in offsets {
+                        if let Some((x, y)) = projection.point(lon + offset, lat, center) {
+                            ctx.print(x, y, "⌂".red().bold());
+                        }
+                    }
+                }
+                if self.marks_visible() {
+                    for (index, mark) in self.marks.iter().enumerate() {
+                        if !self.matches_search(mark) {
+                            continue;
+                        }
+                        let text = match &mark.label {
+                            Some(label) => format!("✚ {label}"),
+                            None => "✚".to_string(),
+                        };
+                        for offset in offsets {
+                            let Some((x, y)) =
+                                projection.point(mark.lon + offset, mark.lat, center)
+                            else {
+                                continue;
+                            };
+                            if self.search_match == Some(index) {
+                                ctx.print(x, y, text.clone().black().on_light_red().bold());
+                            } else {
+                                ctx.print(x, y, text.clone().light_red().bold());
+                            }
+                        }
+                    }
+                }
                 ctx.layer();
                 ctx.layer()
             });
 
-        canvas.render(area, buf)
+        if let Some(snapshot) = self
+            .snapshot
+            .as_ref()
+            .filter(|_| !self.hide_overlays() && !self.layer_paused(Layer::Snapshot))
+        {
+            render_snapshot(snapshot, canvas_area, buf);
+        }
+        canvas.render(area, buf);
+        if let (true, Some(before), Some(after)) = (
+            self.show_snapshot_diff,
+            &self.previous_snapshot,
+            &self.snapshot,
+        ) {
+            render_snapshot_diff(before, after, canvas_area, buf);
+        }
+        if self.circular_clip {
+            clip_to_circle(canvas_area, buf);
+        }
+        if self.show_vignette {
+            render_vignette(canvas_area, buf);
+        }
+        if self.show_ruler {
+            self.render_ruler(canvas_area, buf);
+        }
+        if self.show_extent_labels {
+            self.render_extent_labels(canvas_area, buf);
+        }
+        self.render_debug_overlay(canvas_area, buf, drawn.get());
+    }
+}
+
+impl App {
+    /// Coordinates under the mouse, or dashes when it isn't over the canvas
+    fn cursor_readout(&self) -> String {
+        match self.cursor_coordinate() {
+            Some((lon, lat)) => {
+                let lon = geo::relative_longitude(lon, self.prime_meridian);
+                let precision = self.precision;
+                format!(" lon: {lon:.precision$}  lat: {lat:.precision$} ")
+            }
+            None => " lon: --  lat: -- ".to_string(),
+        }
+    }
+
+    /// Draws meridians and parallels at a spacing following the zoom, the equator
+    /// and the prime meridian brighter than the rest
+    fn draw_graticule(&self, ctx: &mut Context) {
+        let limit = self.projection().max_latitude();
+        let Viewport {
+            min_x,
+            max_x,
+            min_y,
+            max_y,
+            projection,
+            ..
+        } = self.viewport;
+        let step = geo::graticule_step(max_x - min_x, self.graticule_step);
+        let globe = projection == Projection::Orthographic;
+        // the globe shows a whole hemisphere whatever the bounds
+        let (min_x, max_x, min_y, max_y) = if globe {
+            (-180., 180., -limit, limit)
+        } else {
+            (min_x, max_x, min_y.max(-limit), max_y.min(limit))
+        };
+        // and curves the lines, which are followed a couple of degrees at a time
+        let line = |(x1, y1): (f64, f64), (x2, y2): (f64, f64)| -> map::Polyline {
+            let pieces = if globe {
+                ((x2 - x1).abs().max((y2 - y1).abs()) / 2.).ceil().max(1.)
+            } else {
+                1.
+            };
+            (0..=pieces as usize)
+                .map(|index| index as f64 / pieces)
+                .map(|t| (x1 + t * (x2 - x1), y1 + t * (y2 - y1)))
+                .collect()
+        };
+        let mut lines = Vec::new();
+        let mut main_lines = Vec::new();
+        // meridians counted from the prime meridian, like the ruler
+        let offset = self.prime_meridian;
+        // counted in steps rather than accumulated, which would drift off round values
+        let first = ((min_x - offset) / step).ceil() as i64;
+        let last = ((max_x - offset) / step).floor() as i64;
+        for x in (first..=last).map(|index| index as f64 * step) {
+            let line = line((x + offset, min_y), (x + offset, max_y));
+            if geo::relative_longitude(x, 0.).abs() < step / 2. {
+                main_lines.push(line);
+            } else {
+                lines.push(line);
+            }
+        }
+        let first = (min_y / step).ceil() as i64;
+        let last = (max_y / step).floor() as i64;
+        for y in (first..=last).map(|index| index as f64 * step) {
+            let line = line((min_x, y), (max_x, y));
+            if y == 0. {
+                main_lines.push(line);
+            } else {
+                lines.push(line);
+            }
+        }
+        ctx.draw(&map::Polylines {
+            lines: &lines,
+            color: ratatui::style::Color::DarkGray,
+            projection,
+            wrap: false,
+            center: self.viewport.center(),
+        });
+        ctx.draw(&map::Polylines {
+            lines: &main_lines,
+            color: ratatui::style::Color::Gray,
+            projection,
+            wrap: false,
+            center: self.viewport.center(),
+        });
+    }
+
+    /// Draws labelled rings at round distances from the center, out to the edge of
+    /// the view
+    fn draw_range_rings(&self, ctx: &mut Context) {
+        let (lon, lat) = self.viewport.center();
+        let half_height = (self.viewport.max_y - self.viewport.min_y) / 2. * geo::KM_PER_DEGREE;
+        let half_width = (self.viewport.max_x - self.viewport.min_x) / 2.
+            * geo::KM_PER_DEGREE
+            * lat.to_radians().cos();
+        // beyond half the circumference rings shrink again
+        let reach_km = half_height
+            .max(half_width)
+            .min(geo::EARTH_RADIUS_KM * std::f64::consts::PI);
+        let step = geo::nice_step(reach_km, 4.);
+        let precision = geo::step_precision(step);
+        let mut rings = Vec::new();
+        let mut radius = step;
+        while radius < reach_km {
+            rings.extend(geo::circle(lon, lat, radius, 90));
+            let (label_lon, label_lat) = geo::destination(lon, lat, 0., radius);
+            if let Some((x, y)) = self
+                .viewport
+                .projection
+                .point(label_lon, label_lat, (lon, lat))
+            {
+                ctx.print(x, y, format!("{radius:.precision$} km").dark_gray());
+            }
+            radius += step;
+        }
+        ctx.draw(&map::Polylines {
+            lines: &rings,
+            color: ratatui::style::Color::DarkGray,
+            projection: self.viewport.projection,
+            wrap: self.viewport.crosses_antimeridian(),
+            center: (lon, lat),
+        });
+    }
+
+    /// Ticks on the top and left borders with their coordinate just inside the canvas
+    fn render_ruler(&self, area: Rect, buf: &mut Buffer) {
+        // meridians and parallels don't meet the edges of the globe at right angles
+        if area.is_empty()
+            || area.top() == 0
+            || area.left() == 0
+            || self.viewport.projection == Projection::Orthographic
+        {
+            return;
+        }
+        let style = ratatui::style::Style::new().dark_gray();
+        let Viewport {
+            min_x,
+            max_x,
+            min_y,
+            max_y,
+            ..
+        } = self.viewport;
+
+        let step = geo::nice_step(max_x - min_x, f64::from(area.width / 12).max(1.));
+        let precision = geo::step_precision(step);
+        // first free column after the last label, so labels never overlap
+        let mut free = area.left();
+        // ticks fall on round longitudes counted from the prime meridian
+        let offset = self.prime_meridian;
+        let center_lat = self.viewport.latitude_at(0.5);
+        let mut x = ((min_x - offset) / step).ceil() * step;
+        while x + offset <= max_x {
+            let Some((column, _)) = self.viewport.coord_to_pixel(x + offset, center_lat, area)
+            else {
+                break;
+            };
+            let border = &mut buf[(column, area.top() - 1)];
+            if border.symbol() == border::THICK.horizontal_top {
+                border.set_symbol("┳");
+            }
+            if column >= free {
+                // adding zero turns -0 into 0
+                let label = format!("{:.precision$}", geo::relative_longitude(x, 0.) + 0.);
+                buf.set_stringn(
+                    column,
+                    area.top(),
+                    &label,
+                    usize::from(area.right() - column),
+                    style,
+                );
+                free = column + label.len() as u16 + 1;
+            }
+            x += step;
+        }
+
+        let limit = self.viewport.projection.max_latitude();
+        let (min_y, max_y) = (min_y.max(-limit), max_y.min(limit));
+        let step = geo::nice_step(max_y - min_y, f64::from(area.height / 4).max(1.));
+        let precision = geo::step_precision(step);
+        let mut y = (max_y / step).floor() * step;
+        while y >= min_y {
+            let row = self
+                .viewport
+                .coord_to_pixel(min_x, y, area)
+                .map(|(_, row)| row);
+            // the top row holds the longitude labels
+            if let Some(row) = row.filter(|&row| row > area.top()) {
+                let border = &mut buf[(area.left() - 1, row)];
+                if border.symbol() == border::THICK.vertical_left {
+                    border.set_symbol("┣");
+                }
+                buf.set_stringn(
+                    area.left(),
+                    row,
+                    format!("{:.precision$}", y + 0.),
+                    usize::from(area.width),
+                    style,
+                );
+            }
+            y -= step;
+        }
+    }
+
+    fn render_debug_overlay(&self, area: Rect, buf: &mut Buffer, drawn: usize) {
+        if self.debug_overlay == DebugOverlay::Off || area.is_empty() {
+            return;
+        }
+        if self.debug_overlay == DebugOverlay::Checkerboard {
+            for y in area.top()..area.bottom() {
+                for x in area.left()..area.right() {
+                    if (x + y) % 2 == 0 {
+                        buf[(x, y)].set_bg(ratatui::style::Color::Indexed(236));
+                    }
+                }
+            }
+        }
+        let Viewport {
+            min_x,
+            max_x,
+            min_y,
+            max_y,
+            ..
+        } = self.viewport;
+        let style = ratatui::style::Style::new().yellow();
+        let precision = self.precision;
+        let top_left = format!("{min_x:.precision$}, {max_y:.precision$}");
+        let top_right = format!("{max_x:.precision$}, {max_y:.precision$}");
+        let bottom_left = format!("{min_x:.precision$}, {min_y:.precision$}");
+        let bottom_right = format!("{max_x:.precision$}, {min_y:.precision$}");
+        let cell_size = format!(
+            "{:.4}°/cell x  {:.4}°/cell y",
+            (max_x - min_x) / f64::from(area.width),
+            (max_y - min_y) / f64::from(area.height)
+        );
+        let right = |text: &str| area.right().saturating_sub(text.len() as u16);
+        let bottom = area.bottom() - 1;
+        buf.set_string(area.left(), area.top(), &top_left, style);
+        buf.set_string(right(&top_right), area.top(), &top_right, style);
+        buf.set_string(area.left(), bottom, &bottom_left, style);
+        buf.set_string(right(&bottom_right), bottom, &bottom_right, style);
+        if area.height > 3 {
+            buf.set_string(area.left(), area.top() + 1, &cell_size, style);
+            buf.set_string(
+                area.left(),
+                area.top() + 2,
+                format!("{drawn} points drawn"),
+                style,
+            );
+        }
+    }
+
+    /// Labels the corners and edge midpoints of the canvas with their coordinate
+    fn render_extent_labels(&self, area: Rect, buf: &mut Buffer) {
+        // step inside the ruler labels so the two don't overwrite each other
+        let area = if self.show_ruler {
+            area.inner(ratatui::layout::Margin::new(1, 1))
+        } else {
+            area
+        };
+        if area.height < 2 {
+            return;
+        }
+        let style = ratatui::style::Style::new().yellow();
+        let Viewport { min_x, max_x, .. } = self.viewport;
+        let (center_x, _) = self.viewport.center();
+        let precision = self.precision;
+        let label = |lon: f64, lat: f64| {
+            let lon = geo::relative_longitude(lon, self.prime_meridian);
+            format!("{lon:.precision$}, {lat:.precision$}")
+        };
+        // the latitudes along the canvas edges, which the projection keeps off the
+        // poles, and halfway down the canvas, which isn't halfway in latitude
+        let rows = [
+            (area.top(), self.viewport.latitude_at(0.)),
+            (area.top() + area.height / 2, self.viewport.latitude_at(0.5)),
+            (area.bottom() - 1, self.viewport.latitude_at(1.)),
+        ];
+        for (index, (row, lat)) in rows.into_iter().enumerate() {
+            let left = label(min_x, lat);
+            let right = label(max_x, lat);
+            let right_column = area.right().saturating_sub(right.len() as u16);
+            // labels that don't fit side by side are dropped rather than overlapped
+            if left.len() + right.len() >= usize::from(area.width) {
+                continue;
+            }
+            buf.set_string(area.left(), row, &left, style);
+            buf.set_string(right_column, row, &right, style);
+            let center = label(center_x, lat);
+            let center_column = area.left() + area.width.saturating_sub(center.len() as u16) / 2;
+            let fits = usize::from(center_column - area.left()) > left.len()
+                && usize::from(center_column) + center.len() < usize::from(right_column);
+            // the middle row has no midpoint, that's the center of the view
+            if index != 1 && fits {
+                buf.set_string(center_column, row, &center, style);
+            }
+        }
+    }
+}
+
+/// Copies the painted cells of `snapshot` inside `area` into `buf`, dimmed
+fn render_snapshot(snapshot: &Buffer, area: Rect, buf: &mut Buffer) {
+    let area = area.intersection(snapshot.area);
+    for y in area.top()..area.bottom() {
+        for x in area.left()..area.right() {
+            let symbol = snapshot[(x, y)].symbol();
+            if symbol != " " {
+                buf[(x, y)]
+                    .set_symbol(symbol)
+                    .set_fg(ratatui::style::Color::DarkGray);
+            }
+        }
+    }
+}
+
+/// Clears the cells of `area` outside the largest circle that fits in it, taking
+/// cells to be twice as tall as they are wide
+fn clip_to_circle(area: Rect, buf: &mut Buffer) {
+    let radius = (f64::from(area.height) / 2.).min(f64::from(area.width) / 4.);
+    let center_x = f64::from(area.left()) + f64::from(area.width) / 2.;
+    let center_y = f64::from(area.top()) + f64::from(area.height) / 2.;
+    for y in area.top()..area.bottom() {
+        for x in area.left()..area.right() {
+            let dx = (f64::from(x) + 0.5 - center_x) / 2.;
+            let dy = f64::from(y) + 0.5 - center_y;
+            if dx.hypot(dy) > radius {
+                buf[(x, y)].reset();
+            }
+        }
+    }
+}
+
+/// Dims the map cells of `area` more the further they are from its center. Text
+/// such as labels is left alone so it stays readable
+fn render_vignette(area: Rect, buf: &mut Buffer) {
+    let center_x = f64::from(area.left()) + f64::from(area.width) / 2.;
+    let center_y = f64::from(area.top()) + f64::from(area.height) / 2.;
+    for y in area.top()..area.bottom() {
+        for x in area.left()..area.right() {
+            let cell = &mut buf[(x, y)];
+            let is_braille = cell
+                .symbol()
+                .chars()
+                .next()
+                .is_some_and(|c| ('\u{2801}'..='\u{28ff}').contains(&c));
+            let Some((r, g, b)) = rgb(cell.fg).filter(|_| is_braille) else {
+                continue;
+            };
+            // 0 at the center and 1 in the corners
+            let dx = (f64::from(x) + 0.5 - center_x) / (f64::from(area.width) / 2.);
+            let dy = (f64::from(y) + 0.5 - center_y) / (f64::from(area.height) / 2.);
+            let distance = dx.hypot(dy) / std::f64::consts::SQRT_2;
+            let brightness = 1. - VIGNETTE_STRENGTH * distance.powi(2);
+            let scale = |channel: u8| (f64::from(channel) * brightness).round() as u8;
+            cell.set_fg(ratatui::style::Color::Rgb(scale(r), scale(g), scale(b)));
+        }
+    }
+}
+
+/// Approximate RGB value of a color, using the common xterm palette for the named
+/// colors
+fn rgb(color: ratatui::style::Color) -> Option<(u8, u8, u8)> {
+    use ratatui::style::Color;
+    Some(match color {
+        Color::Black => (0, 0, 0),
+        Color::Red => (205, 0, 0),
+        Color::Green => (0, 205, 0),
+        Color::Yellow => (205, 205, 0),
+        Color::Blue => (0, 0, 238),
+        Color::Magenta => (205, 0, 205),
+        Color::Cyan => (0, 205, 205),
+        Color::Gray => (229, 229, 229),
+        Color::DarkGray => (127, 127, 127),
+        Color::LightRed => (255, 0, 0),
+        Color::LightGreen => (0, 255, 0),
+        Color::LightYellow => (255, 255, 0),
+        Color::LightBlue => (92, 92, 255),
+        Color::LightMagenta => (255, 0, 255),
+        Color::LightCyan => (0, 255, 255),
+        Color::White => (255, 255, 255),
+        Color::Rgb(r, g, b) => (r, g, b),
+        Color::Reset | Color::Indexed(_) => return None,
+    })
+}
+
+/// Replaces `area` of `buf` with the `after` snapshot, cells that differ from
+/// `before` in red and the rest dimmed
+fn render_snapshot_diff(before: &Buffer, after: &Buffer, area: Rect, buf: &mut Buffer) {
+    let area = area.intersection(before.area).intersection(after.area);
+    for y in area.top()..area.bottom() {
+        for x in area.left()..area.right() {
+            let color = if before[(x, y)] == after[(x, y)] {
+                ratatui::style::Color::DarkGray
+            } else {
+                ratatui::style::Color::Red
+            };
+            let cell = &mut buf[(x, y)];
+            cell.reset();
+            cell.set_symbol(after[(x, y)].symbol()).set_fg(color);
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use ratatui::style::Style;
+    use ratatui::style::{Color, Modifier};
 
     #[test]
     fn render() {
         let app = App::default();
-        let mut buf = Buffer::empty(Rect::new(0, 0, 50, 4));
+        let mut buf = Buffer::empty(Rect::new(0, 0, 240, 6));
 
         app.render(buf.area, &mut buf);
 
-        let mut expected = Buffer::with_lines(vec![
-            "┏━━━━━━━━━━━━━ Counter App Tutorial ━━━━━━━━━━━━━┓",
-            "┃                    Value: 0                    ┃",
-            "┃                                                ┃",
-            "┗━ Decrement <Left> Increment <Right> Quit <Q> ━━┛",
-        ]);
-        let title_style = Style::new().bold();
-        let counter_style = Style::new().yellow();
-        let key_style = Style::new().blue().bold();
-        expected.set_style(Rect::new(14, 0, 22, 1), title_style);
-        expected.set_style(Rect::new(28, 1, 1, 1), counter_style);
-        expected.set_style(Rect::new(13, 3, 6, 1), key_style);
-        expected.set_style(Rect::new(30, 3, 7, 1), key_style);
-        expected.set_style(Rect::new(43, 3, 4, 1), key_style);
+        let row = |buf: &Buffer, y| {
+            (0..buf.area.width)
+                .map(|x| buf[(x, y)].symbol())
+                .collect::<String>()
+        };
+        let (top, bottom) = (row(&buf, 0), row(&buf, 5));
+        assert!(top.starts_with('┏') && top.contains(" Map "), "{top}");
+        assert!(
+            bottom.starts_with('┗') && bottom.contains(" Zoom In <Up"),
+            "{bottom}"
+        );
+        assert!(bottom.contains(" Quit <q> "), "{bottom}");
+        // the keys stand out from what they do
+        let key = bottom.chars().position(|c| c == '<').unwrap() as u16;
+        assert_eq!(buf[(key, 5)].fg, Color::Blue);
+        assert!(buf[(key, 5)].modifier.contains(Modifier::BOLD));
 
-        // note ratatui also has an assert_buffer_eq! macro that can be used to
-        // compare buffers and display the differences in a more readable way
-        assert_eq!(buf, expected);
+        // the hint follows --quit-key
+        let app = App {
+            quit_key: None,
+            ..App::default()
+        };
+        app.render(buf.area, &mut buf);
+        let bottom = row(&buf, 5);
+        assert!(bottom.contains(" Quit <Ctrl+C> "), "{bottom}");
     }
 
     #[test]
-    fn handle_key_event() -> io::Result<()> {
+    fn handle_key_event() {
         let mut app = App::default();
-        app.handle_key_event(KeyCode::Right.into());
-        assert_eq!(app.counter, 1);
+        app.handle_key_event(KeyCode::Char('q').into()).unwrap();
+        assert!(app.exited());
 
-        app.handle_key_event(KeyCode::Left.into());
-        assert_eq!(app.counter, 0);
+        let mut app = App {
+            quit_key: None,
+            ..App::default()
+        };
+        app.handle_key_event(KeyCode::Char('q').into()).unwrap();
+        assert!(!app.exit);
+        app.handle_key_event(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL))
+            .unwrap();
+        assert!(app.exit);
+    }
 
+    #[test]
+    fn failed_open_command_is_reported() {
+        let mut app = App {
+            open_command: Some(vec!["/nonexistent/plou-open".into(), "{lat}".into()]),
+            ..App::default()
+        };
+        app.handle_key_event(KeyCode::Char('o').into()).unwrap();
+        assert!(!app.exited());
+        let message = app.status_message.unwrap();
+        assert!(
+            message.starts_with("failed to run /nonexistent/plou-open"),
+            "{message}"
+        );
+    }
+
+    #[test]
+    fn bbox_is_copied() {
+        let mut app = App {
+            viewport: Viewport::from_bounds(-10., -5., 10., 5.),
+            precision: 0,
+            ..App::default()
+        };
+        app.handle_key_event(KeyCode::Char('B').into()).unwrap();
+        assert_eq!(app.clipboard.as_deref(), Some("-10,-5,10,5"));
+        assert_eq!(app.status_message.as_deref(), Some("-10,-5,10,5 copied"));
+        assert_eq!(app.bbox_format, BboxFormat::GeoJson);
+    }
+
+    #[test]
+    fn export_keeps_existing_manifest() {
+        let path = std::env::temp_dir().join("plou-export.toml");
+        let _ = std::fs::remove_file(&path);
         let mut app = App::default();
-        app.handle_key_event(KeyCode::Char('q').into());
-        assert!(app.exit);
+        app.set_projection(Projection::Mercator);
+        app.export_manifest(&path);
+        assert!(app.status_message.as_ref().unwrap().starts_with("wrote"));
+        let written = std::fs::read_to_string(&path).unwrap();
+        assert!(written.contains("projection = \"mercator\""), "{written}");
 
-        Ok(())
+        app.set_projection(Projection::Equirectangular);
+        app.export_manifest(&path);
+        assert!(app.status_message.unwrap().contains("already exists"));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), written);
+    }
+
+    #[test]
+    fn zoom_stops_at_limits() {
+        let mut viewport = Viewport::default();
+        assert!(!viewport.zoom(-1));
+        assert_eq!(viewport, Viewport::default());
+
+        for _ in 0..2000 {
+            viewport.zoom(1);
+        }
+        assert!(viewport.max_x - viewport.min_x >= MIN_SPAN);
+        assert!(viewport.max_y - viewport.min_y >= MIN_SPAN);
+        assert!(!viewport.zoom(1));
+
+        for _ in 0..2000 {
+            viewport.zoom(-1);
+        }
+        assert!(viewport.max_x - viewport.min_x <= 360.);
+        assert!(viewport.zoom(1));
+    }
+
+    #[test]
+    fn pan_stops_at_poles_and_wraps_around() {
+        let mut app = App {
+            viewport: Viewport::from_bounds(100., 0., 160., 30.),
+            ..App::default()
+        };
+        app.pan_by(50., 80.);
+        assert_eq!(app.viewport.bounds(), [150., 60., 210., 90.]);
+        assert!(app.viewport.crosses_antimeridian());
+
+        // the center crosses the antimeridian and the view comes around the world
+        app.pan_by(20., 0.);
+        assert_eq!(app.viewport.bounds(), [-190., 60., -130., 90.]);
+
+        let mut viewport = Viewport::from_bounds(-150., -100., 250., 60.);
+        viewport.clamp();
+        assert_eq!(viewport.bounds(), [-200., -90., 200., 70.]);
+    }
+
+    #[test]
+    fn pan_step_follows_zoom() {
+        let mut app = App {
+            viewport: Viewport::from_bounds(-100., -50., 100., 50.),
+            ..App::default()
+        };
+        app.handle_key_event(KeyCode::Char('d').into()).unwrap();
+        assert_eq!(app.viewport.min_x, -80.);
+
+        app.viewport = Viewport::from_bounds(0., 0., 10., 5.);
+        app.handle_key_event(KeyCode::Char('w').into()).unwrap();
+        assert_eq!(app.viewport.bounds(), [0., 0.5, 10., 5.5]);
+    }
+
+    #[test]
+    fn scroll_zooms_toward_cursor() {
+        let mut app = App::default();
+        let area = Rect::new(0, 0, 90, 45);
+        app.canvas_area.set(area);
+        let (column, row) = (10, 30);
+        let before = app.viewport.pixel_to_coord(column, row, area).unwrap();
+
+        app.handle_mouse_event(MouseEvent {
+            kind: MouseEventKind::ScrollUp,
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        })
+        .unwrap();
+
+        let after = app.viewport.pixel_to_coord(column, row, area).unwrap();
+        assert!(app.viewport.max_x - app.viewport.min_x < 360.);
+        assert!((before.0 - after.0).abs() < 1e-9 && (before.1 - after.1).abs() < 1e-9);
+    }
+
+    #[test]
+    fn cursor_readout() {
+        let mut app = App {
+            precision: 1,
+            ..App::default()
+        };
+        app.canvas_area.set(Rect::new(1, 1, 36, 18));
+        assert_eq!(app.cursor_readout(), " lon: --  lat: -- ");
+
+        app.handle_mouse_event(MouseEvent {
+            kind: MouseEventKind::Moved,
+            column: 1,
+            row: 1,
+            modifiers: KeyModifiers::NONE,
+        })
+        .unwrap();
+        assert_eq!(app.cursor_readout(), " lon: -175.0  lat: 85.0 ");
+
+        app.cursor = Some((0, 0));
+        assert_eq!(app.cursor_readout(), " lon: --  lat: -- ");
+    }
+
+    #[test]
+    fn pan_north_stops_at_pole() {
+        let mut app = App {
+            viewport: Viewport::from_bounds(-20., 60., 20., 80.),
+            ..App::default()
+        };
+        for _ in 0..10 {
+            app.handle_key_event(KeyCode::Char('w').into()).unwrap();
+        }
+        assert_eq!((app.viewport.min_y, app.viewport.max_y), (70., 90.));
+
+        app.viewport.center_on(0., 89.);
+        assert_eq!((app.viewport.min_y, app.viewport.max_y), (70., 90.));
+    }
+
+    #[test]
+    fn reset_key() {
+        let mut app = App {
+            viewport: Viewport::from_bounds(10., 10., 20., 15.),
+            last_mouse_drag_position: Some((3, 4)),
+            drag_remainder: (0.5, 0.25),
+            held_zoom: Some((1, Instant::now())),
+            ..App::default()
+        };
+        app.handle_key_event(KeyCode::Char('r').into()).unwrap();
+        assert_eq!(app.viewport, Viewport::default());
+        assert_eq!(app.last_mouse_drag_position, None);
+        assert_eq!(app.drag_remainder, (0., 0.));
+        assert_eq!(app.held_zoom, None);
+    }
+
+    #[test]
+    fn inspect_across_antimeridian() {
+        let mut app = App {
+            viewport: Viewport::from_bounds(160., -10., 200., 10.),
+            layers: vec![MapLayer {
+                data: LayerData::Points(Points {
+                    coordinates: vec![(-175., 0.)],
+                    breaks: Vec::new(),
+                }),
+                ..MapLayer::world(ratatui::style::Color::Blue)
+            }],
+            inspect_mode: true,
+            ..App::default()
+        };
+        let mut buf = Buffer::empty(Rect::new(0, 0, 42, 22));
+        app.render(buf.area, &mut buf);
+        // 185 east is 175 west a turn later
+        app.handle_mouse_event(MouseEvent {
+            kind: MouseEventKind::Moved,
+            column: 26,
+            row: 11,
+            modifiers: KeyModifiers::NONE,
+        })
+        .unwrap();
+        assert_eq!(app.nearest_coastline(), Some((0, (-175., 0.))));
+
+        app.render(buf.area, &mut buf);
+        assert!(buf.content().iter().any(|cell| cell.symbol() == "◆"));
+    }
+
+    #[test]
+    fn scroll_zoom_reaches_limits() {
+        let mut viewport = Viewport::from_bounds(-170., -85., 170., 85.);
+        assert!(viewport.zoom_at(1. / SCROLL_ZOOM_FACTOR, 0., 0.));
+        assert_eq!(viewport.bounds(), [-180., -90., 180., 90.]);
+        assert!(!viewport.zoom_at(1. / SCROLL_ZOOM_FACTOR, 0., 0.));
+
+        let mut viewport = Viewport::from_bounds(0., 0., 0.55, 0.55);
+        assert!(viewport.zoom_at(SCROLL_ZOOM_FACTOR, 0., 0.));
+        assert!((viewport.max_x - MIN_SPAN).abs() < 1e-9);
+        assert!(!viewport.zoom_at(SCROLL_ZOOM_FACTOR, 0., 0.));
+    }
+
+    #[test]
+    fn scrolling_out_stops_at_the_world() {
+        let mut app = App {
+            viewport: Viewport::from_bounds(10., 10., 11., 11.),
+            ..App::default()
+        };
+        app.canvas_area.set(Rect::new(0, 0, 36, 18));
+        let scroll = |kind| MouseEvent {
+            kind,
+            column: 3,
+            row: 15,
+            modifiers: KeyModifiers::NONE,
+        };
+        for _ in 0..200 {
+            app.handle_mouse_event(scroll(MouseEventKind::ScrollDown))
+                .unwrap();
+            assert!(app.viewport.max_x - app.viewport.min_x <= MAX_SPAN.0 + 1e-9);
+            assert!(app.viewport.max_y - app.viewport.min_y <= MAX_SPAN.1 + 1e-9);
+        }
+        // the view keeps its shape, so only its height reaches the whole world
+        assert!((app.viewport.max_y - app.viewport.min_y - MAX_SPAN.1).abs() < 1e-9);
+
+        for _ in 0..200 {
+            app.handle_mouse_event(scroll(MouseEventKind::ScrollUp))
+                .unwrap();
+            assert!(app.viewport.max_x - app.viewport.min_x >= MIN_SPAN - 1e-9);
+        }
+    }
+
+    #[test]
+    fn mercator_canvas() {
+        let mut app = App::default();
+        app.handle_key_event(KeyCode::Char('p').into()).unwrap();
+        assert_eq!(app.viewport.projection, Projection::Mercator);
+
+        let area = Rect::new(0, 0, 36, 36);
+        let (_, top) = app.viewport.pixel_to_coord(0, 0, area).unwrap();
+        let (_, middle) = app.viewport.pixel_to_coord(0, 17, area).unwrap();
+        assert!(top < 85. && top > 80.);
+        // rows near the equator cover more latitude than rows near the edges
+        assert!(middle > 0. && middle < 5.);
+        let (_, row) = app.viewport.coord_to_pixel(0., 40., area).unwrap();
+        let (_, lat) = app.viewport.pixel_to_coord(0, row, area).unwrap();
+        assert!((lat - 40.).abs() < 3.);
+    }
+
+    #[test]
+    fn orthographic_canvas() {
+        let mut app = App::default();
+        app.handle_key_event(KeyCode::Char('p').into()).unwrap();
+        app.handle_key_event(KeyCode::Char('p').into()).unwrap();
+        assert_eq!(app.viewport.projection, Projection::Orthographic);
+        app.viewport.center_on(30., 45.);
+
+        let area = Rect::new(0, 0, 36, 18);
+        let (lon, lat) = app.viewport.pixel_to_coord(18, 9, area).unwrap();
+        assert!((lon - 30.).abs() < 10. && (lat - 45.).abs() < 10.);
+        assert_eq!(app.viewport.coord_to_pixel(-150., 0., area), None);
+        // the far side of the globe can't be pointed at
+        assert_eq!(app.viewport.pixel_to_coord(0, 0, area), None);
+    }
+
+    #[test]
+    fn right_click_adds_mark() {
+        let mut app = App::default();
+        app.canvas_area.set(Rect::new(0, 0, 36, 18));
+        let click = |column, row| MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Right),
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        };
+
+        app.handle_mouse_event(click(18, 9)).unwrap();
+        // outside the canvas
+        app.handle_mouse_event(click(40, 9)).unwrap();
+
+        assert_eq!(app.marks.len(), 1);
+        assert!((app.marks[0].lon - 5.).abs() < 1e-9 && (app.marks[0].lat + 5.).abs() < 1e-9);
+
+        // past the antimeridian the mark lands a turn west, where the map draws it
+        app.viewport = Viewport::from_bounds(160., -10., 200., 10.);
+        app.canvas_area.set(Rect::new(0, 0, 40, 20));
+        app.handle_mouse_event(click(35, 10)).unwrap();
+        assert!(
+            (app.marks[1].lon + 164.5).abs() < 1e-9,
+            "{}",
+            app.marks[1].lon
+        );
+
+        app.inspect_mode = true;
+        app.handle_mouse_event(MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            ..click(35, 10)
+        })
+        .unwrap();
+        let (lon, _) = app.pinned_coordinate().unwrap();
+        assert!((lon + 164.5).abs() < 1e-9, "{lon}");
+    }
+
+    #[test]
+    fn coord_pixel_round_trip() {
+        let area = Rect::new(2, 1, 36, 18);
+        for projection in [Projection::Equirectangular, Projection::Mercator] {
+            let viewport = Viewport {
+                projection,
+                ..Viewport::from_bounds(-90., -45., 90., 45.)
+            };
+            for (column, row) in [(2, 1), (20, 9), (37, 18)] {
+                let (lon, lat) = viewport.pixel_to_coord(column, row, area).unwrap();
+                assert_eq!(viewport.coord_to_pixel(lon, lat, area), Some((column, row)));
+            }
+            assert_eq!(viewport.coord_to_pixel(100., 0., area), None);
+            assert_eq!(viewport.coord_to_pixel(0., -50., area), None);
+        }
+    }
+
+    #[test]
+    fn drag_follows_cursor() {
+        let mut app = App {
+            viewport: Viewport::from_bounds(-90., -45., 90., 45.),
+            ..App::default()
+        };
+        app.canvas_area.set(Rect::new(0, 0, 36, 18));
+        let drag = |column, row| MouseEvent {
+            kind: MouseEventKind::Drag(MouseButton::Left),
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        };
+
+        app.handle_mouse_event(drag(10, 5)).unwrap();
+        app.handle_mouse_event(drag(13, 6)).unwrap();
+
+        // 5 degrees per cell, the map moves 3 cells east and 1 south
+        assert!((app.viewport.min_x + 105.).abs() < 1e-9);
+        assert!((app.viewport.max_y - 50.).abs() < 1e-9);
+
+        // without losing the motion before the first drag event
+        app.handle_mouse_event(MouseEvent {
+            kind: MouseEventKind::Up(MouseButton::Left),
+            ..drag(13, 6)
+        })
+        .unwrap();
+        app.handle_mouse_event(MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            ..drag(13, 6)
+        })
+        .unwrap();
+        app.handle_mouse_event(drag(11, 6)).unwrap();
+        assert!((app.viewport.min_x + 95.).abs() < 1e-9);
+    }
+
+    #[test]
+    fn click_recenters() {
+        let mut app = App {
+            viewport: Viewport::from_bounds(-90., -45., 90., 45.),
+            ..App::default()
+        };
+        app.canvas_area.set(Rect::new(0, 0, 36, 18));
+        let event = |kind, column, row| MouseEvent {
+            kind,
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        };
+
+        app.handle_mouse_event(event(MouseEventKind::Down(MouseButton::Left), 24, 6))
+            .unwrap();
+        app.handle_mouse_event(event(MouseEventKind::Up(MouseButton::Left), 24, 6))
+            .unwrap();
+        // the center of the cell 6 columns right and 3 rows up of the middle
+        assert!((app.viewport.min_x + 57.5).abs() < 1e-9);
+        assert!((app.viewport.max_y - 57.5).abs() < 1e-9);
+
+        // a drag pans without recentering on release
+        let start = app.viewport.clone();
+        app.handle_mouse_event(event(MouseEventKind::Down(MouseButton::Left), 10, 5))
+            .unwrap();
+        app.handle_mouse_event(event(MouseEventKind::Drag(MouseButton::Left), 10, 5))
+            .unwrap();
+        app.handle_mouse_event(event(MouseEventKind::Drag(MouseButton::Left), 12, 5))
+            .unwrap();
+        app.handle_mouse_event(event(MouseEventKind::Up(MouseButton::Left), 12, 5))
+            .unwrap();
+        assert!((app.viewport.min_x - (start.min_x - 10.)).abs() < 1e-9);
+        assert_eq!(app.viewport.max_y, start.max_y);
+    }
+
+    #[test]
+    fn graticule_beneath_map() {
+        use ratatui::style::Color;
+        let mut app = App {
+            show_graticule: true,
+            layers: Vec::new(),
+            ..App::default()
+        };
+        let mut buf = Buffer::empty(Rect::new(0, 0, 74, 20));
+        app.render(buf.area, &mut buf);
+        assert!(buf.content().iter().any(|cell| cell.fg == Color::DarkGray));
+        // the equator
+        assert!(buf.content().iter().any(|cell| cell.fg == Color::Gray));
+
+        app.show_graticule = false;
+        let mut buf = Buffer::empty(Rect::new(0, 0, 74, 20));
+        app.render(buf.area, &mut buf);
+        assert!(!buf.content().iter().any(|cell| cell.fg == Color::Gray));
+    }
+
+    #[test]
+    fn simplify_on_first_toggle() {
+        let mut app = App::default();
+        let mut buf = Buffer::empty(Rect::new(0, 0, 74, 20));
+        app.render(buf.area, &mut buf);
+        assert!(app.simplified.get().is_none());
+
+        app.handle_key_event(KeyCode::Char('S').into()).unwrap();
+        app.render(buf.area, &mut buf);
+        assert!(app.simplified.get().is_some());
+    }
+
+    #[test]
+    fn search_marks() {
+        let mark = |lon, label: &str| Mark {
+            lon,
+            lat: 0.,
+            label: Some(label.to_string()),
+        };
+        let mut app = App {
+            marks: vec![mark(10., "Quito"), mark(20., "Paris"), mark(30., "Quebec")],
+            ..App::default()
+        };
+        for c in "/qu".chars() {
+            app.handle_key_event(KeyCode::Char(c).into()).unwrap();
+        }
+        assert!(!app.exited());
+        assert!(!app.matches_search(&app.marks[1]));
+
+        app.handle_key_event(KeyCode::Enter.into()).unwrap();
+        assert_eq!(app.search_match, Some(0));
+        assert_eq!(app.viewport.center().0, 10.);
+        app.handle_key_event(KeyCode::Enter.into()).unwrap();
+        assert_eq!(app.search_match, Some(2));
+        app.handle_key_event(KeyCode::Enter.into()).unwrap();
+        assert_eq!(app.search_match, Some(0));
+
+        app.handle_key_event(KeyCode::Esc.into()).unwrap();
+        assert!(app.search.is_none());
+        assert!(app.matches_search(&app.marks[1]));
+    }
+
+    #[test]
+    fn shift_arrows_pan() {
+        let mut app = App {
+            viewport: Viewport::from_bounds(-90., -45., 90., 45.),
+            ..App::default()
+        };
+        app.canvas_area.set(Rect::new(0, 0, 90, 45));
+        let start = app.viewport().clone();
+
+        app.handle_key_event(KeyEvent::new(KeyCode::Up, KeyModifiers::SHIFT))
+            .unwrap();
+        assert!(app.viewport().min_y > start.min_y);
+        assert_eq!(app.viewport().min_x, start.min_x);
+        assert_eq!(app.viewport().zoom_level, start.zoom_level);
+
+        app.handle_key_event(KeyEvent::new(KeyCode::Left, KeyModifiers::SHIFT))
+            .unwrap();
+        assert!(app.viewport().min_x < start.min_x);
+
+        app.handle_key_event(KeyCode::Up.into()).unwrap();
+        assert!(app.viewport().zoom_level > start.zoom_level);
+    }
+
+    #[test]
+    fn vim_keys_pan_and_zoom() {
+        let start = Viewport::from_bounds(-90., -45., 90., 45.);
+        let mut app = App {
+            viewport: start.clone(),
+            ..App::default()
+        };
+        for key in ['h', 'j', 'k', 'l'] {
+            app.handle_key_event(KeyCode::Char(key).into()).unwrap();
+            let moved = app.viewport().clone();
+            match key {
+                'h' => assert!(moved.min_x < start.min_x && moved.min_y == start.min_y),
+                'j' => assert!(moved.min_y < start.min_y && moved.min_x == start.min_x),
+                'k' => assert!(moved.min_y > start.min_y && moved.min_x == start.min_x),
+                _ => assert!(moved.min_x > start.min_x && moved.min_y == start.min_y),
+            }
+            app.set_viewport(start.clone());
+        }
+
+        app.handle_key_event(KeyCode::Char('+').into()).unwrap();
+        assert!(app.viewport().zoom_level > start.zoom_level);
+        app.set_viewport(start.clone());
+        app.handle_key_event(KeyCode::Char('-').into()).unwrap();
+        assert!(app.viewport().zoom_level < start.zoom_level);
+    }
+
+    #[test]
+    fn snapshot_diff_marks_changed_cells() {
+        let before = Buffer::with_lines(["ab", "cd"]);
+        let after = Buffer::with_lines(["ab", "xd"]);
+        let mut buf = Buffer::with_lines(["..", ".."]);
+
+        render_snapshot_diff(&before, &after, buf.area, &mut buf);
+
+        assert_eq!(buf[(0, 1)].symbol(), "x");
+        assert_eq!(buf[(0, 1)].fg, ratatui::style::Color::Red);
+        assert_eq!(buf[(1, 1)].fg, ratatui::style::Color::DarkGray);
+    }
+
+    #[test]
+    fn extent_labels() {
+        let app = App {
+            precision: 4,
+            ..App::default()
+        };
+        let mut buf = Buffer::empty(Rect::new(0, 0, 80, 5));
+
+        app.render_extent_labels(buf.area, &mut buf);
+
+        let row = |y: u16| -> String { (0..80).map(|x| buf[(x, y)].symbol()).collect() };
+        assert!(row(0).starts_with("-180.0000, 90.0000"));
+        assert!(row(0).contains("0.0000, 90.0000"));
+        assert!(row(2).ends_with("180.0000, 0.0000"));
+        assert!(row(4).starts_with("-180.0000, -90.0000"));
+    }
+
+    #[test]
+    fn fit_pin_and_home() {
+        let mut app = App {
+            pinned_coordinate: Some((10., 40.)),
+            home: Some((-20., 30.)),
+            ..App::default()
+        };
+
+        app.fit_pin_and_home();
+
+        let Viewport {
+            min_x,
+            max_x,
+            min_y,
+            max_y,
+            ..
+        } = app.viewport;
+        assert!(min_x < -20. && max_x > 10. && min_y < 30. && max_y > 40.);
+        assert!(max_x - min_x < 40.);
+
+        // the short way across the antimeridian
+        app.pinned_coordinate = Some((170., 0.));
+        app.home = Some((-170., 10.));
+        app.fit_pin_and_home();
+        let ([min_x, _, max_x, _], center) = (app.viewport.bounds(), app.viewport.center());
+        assert!(max_x - min_x < 30., "{min_x} {max_x}");
+        assert!(app.viewport.crosses_antimeridian());
+        assert!((center.0.abs() - 180.).abs() < 1e-9, "{center:?}");
+    }
+
+    #[test]
+    fn circular_clip() {
+        let mut buf = Buffer::with_lines(vec!["#".repeat(8); 4]);
+
+        clip_to_circle(buf.area, &mut buf);
+
+        assert_eq!(buf[(0, 0)].symbol(), " ");
+        assert_eq!(buf[(7, 3)].symbol(), " ");
+        assert_eq!(buf[(4, 2)].symbol(), "#");
+    }
+
+    #[test]
+    fn vignette_dims_edges_only() {
+        let mut buf = Buffer::with_lines(["⣿   ", "  ⣿ ", "   a"]);
+        buf.set_style(buf.area, ratatui::style::Style::new().white());
+
+        render_vignette(buf.area, &mut buf);
+
+        let red = |x: u16, y: u16| match buf[(x, y)].fg {
+            ratatui::style::Color::Rgb(r, _, _) => r,
+            color => panic!("unexpected color {color:?}"),
+        };
+        assert!(red(0, 0) < red(2, 1));
+        // text keeps its color
+        assert_eq!(buf[(3, 2)].fg, ratatui::style::Color::White);
+    }
+
+    #[test]
+    fn held_zoom_stops_on_release() {
+        let mut app = App {
+            continuous_zoom: true,
+            ..App::default()
+        };
+
+        app.handle_key_event(KeyCode::Up.into()).unwrap();
+        assert!(matches!(app.held_zoom, Some((1, _))));
+        assert!(app.viewport().zoom_level > 0.);
+
+        app.handle_key_release(KeyEvent::new_with_kind(
+            KeyCode::Up,
+            KeyModifiers::NONE,
+            KeyEventKind::Release,
+        ));
+        assert_eq!(app.held_zoom, None);
+
+        // already showing the whole world
+        app.viewport = Viewport::default();
+        app.handle_key_event(KeyCode::Down.into()).unwrap();
+        assert_eq!(app.held_zoom, None);
+    }
+
+    #[test]
+    fn zoom_in_never_inverts() {
+        let mut app = App::default();
+        for _ in 0..200 {
+            app.increment_zoom().unwrap();
+        }
+        let viewport = app.viewport();
+        assert!(viewport.min_x < viewport.max_x && viewport.min_y < viewport.max_y);
+    }
+
+    #[test]
+    fn cycle_resolution() {
+        let mut app = App::default();
+        assert_eq!(app.resolution(), WorldResolution::Low);
+
+        app.handle_key_event(KeyCode::Char('m').into()).unwrap();
+        assert_eq!(app.resolution(), WorldResolution::Med);
+        app.handle_key_event(KeyCode::Char('m').into()).unwrap();
+        app.handle_key_event(KeyCode::Char('m').into()).unwrap();
+        assert_eq!(app.resolution(), WorldResolution::Low);
+    }
+
+    #[test]
+    fn locked_axis_does_not_pan() {
+        let mut app = App {
+            lock_x: true,
+            viewport: Viewport::from_bounds(-90., -45., 90., 45.),
+            ..App::default()
+        };
+        let start = app.viewport.clone();
+
+        app.pan_by(10., 5.);
+        assert_eq!(app.viewport.min_x, start.min_x);
+        assert_eq!(app.viewport.min_y, start.min_y + 5.);
     }
 }
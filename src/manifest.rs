@@ -0,0 +1,288 @@
+use std::path::{Path, PathBuf};
+
+use color_eyre::{
+    eyre::{bail, eyre, WrapErr},
+    Result,
+};
+use ratatui::style::Color;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    geo::Projection,
+    loader,
+    map::{LayerData, LineFormat, MapLayer},
+};
+
+/// Ordered list of layers to load at startup, read from a TOML file with one
+/// `[[layer]]` table per layer
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Manifest {
+    #[serde(default, rename = "layer")]
+    pub layers: Vec<LayerSpec>,
+    /// initial view, the whole world if missing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub view: Option<ViewSpec>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ViewSpec {
+    /// [min_lon, min_lat, max_lon, max_lat]
+    pub bounds: [f64; 4],
+    /// `--projection` takes precedence over it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub projection: Option<Projection>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct LayerSpec {
+    #[serde(rename = "type")]
+    pub kind: LayerKind,
+    /// data file, relative paths are resolved from the manifest's directory
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+    /// color name (`blue`), index (`33`) or hex (`#ff8800`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    /// character to draw the points with, accepted only to reject it clearly since
+    /// every layer is drawn with braille dots
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub glyph: Option<String>,
+    #[serde(default = "visible_by_default")]
+    pub visible: bool,
+}
+
+fn visible_by_default() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LayerKind {
+    World,
+    Wkt,
+    GeoJson,
+    /// `lat lon` lines drawn like the world coastline
+    Points,
+    /// not supported, recognized to point to `points` and `--markers` instead
+    Csv,
+    /// GPX tracks, not supported yet
+    Gpx,
+}
+
+impl LayerKind {
+    fn default_color(self) -> Color {
+        match self {
+            Self::World | Self::Points => Color::Blue,
+            Self::Wkt | Self::GeoJson | Self::Csv | Self::Gpx => Color::Yellow,
+        }
+    }
+}
+
+impl Manifest {
+    /// Manifest reproducing `layers` seen through `bounds` with `projection`
+    pub fn new(layers: &[MapLayer], bounds: [f64; 4], projection: Projection) -> Self {
+        let layers = layers
+            .iter()
+            .map(|layer| LayerSpec {
+                kind: match layer.data {
+                    LayerData::World => LayerKind::World,
+                    LayerData::Lines(_, LineFormat::Wkt) => LayerKind::Wkt,
+                    LayerData::Lines(_, LineFormat::GeoJson) => LayerKind::GeoJson,
+                    LayerData::Points(_) => LayerKind::Points,
+                },
+                path: layer.path.clone(),
+                color: Some(layer.color.to_string()),
+                glyph: None,
+                visible: layer.visible,
+            })
+            .collect();
+        Self {
+            layers,
+            view: Some(ViewSpec {
+                bounds,
+                projection: Some(projection),
+            }),
+        }
+    }
+
+    /// Loads the data of every layer, `dir` is the directory of the manifest
+    pub fn into_layers(self, dir: &Path) -> Result<Vec<MapLayer>> {
+        self.layers
+            .into_iter()
+            .enumerate()
+            .map(|(index, spec)| {
+                spec.into_layer(dir)
+                    .wrap_err_with(|| format!("invalid layer {}", index + 1))
+            })
+            .collect()
+    }
+}
+
+impl LayerSpec {
+    fn into_layer(self, dir: &Path) -> Result<MapLayer> {
+        if let Some(glyph) = &self.glyph {
+            bail!("glyph `{glyph}` is not supported, layers are drawn with braille dots");
+        }
+        let color = match &self.color {
+            Some(color) => color
+                .parse()
+                .map_err(|_| eyre!("unknown color `{color}`"))?,
+            None => self.kind.default_color(),
+        };
+        let (name, path, data) = match self.kind {
+            LayerKind::World => ("world".to_string(), None, LayerData::World),
+            LayerKind::Wkt => {
+                let path = dir.join(self.path.ok_or_else(|| eyre!("wkt layers need a path"))?);
+                let wkt = loader::load_wkt(&path)?;
+                for tag in wkt.skipped {
+                    eprintln!("skipping unsupported WKT geometry {tag}");
+                }
+                (
+                    path.display().to_string(),
+                    Some(path),
+                    LayerData::Lines(wkt.polylines, LineFormat::Wkt),
+                )
+            }
+            LayerKind::GeoJson => {
+                let path = dir.join(
+                    self.path
+                        .ok_or_else(|| eyre!("geojson layers need a path"))?,
+                );
+                let polylines = loader::load_geojson(&path)?;
+                (
+                    path.display().to_string(),
+                    Some(path),
+                    LayerData::Lines(polylines, LineFormat::GeoJson),
+                )
+            }
+            LayerKind::Csv => bail!(
+                "csv layers are not supported, use `points` for `lat lon` files or \
+                 --markers for `lon,lat,label` rows"
+            ),
+            LayerKind::Gpx => bail!("gpx layers are not supported, convert the tracks to GeoJSON"),
+            LayerKind::Points => {
+                let path = dir.join(
+                    self.path
+                        .ok_or_else(|| eyre!("points layers need a path"))?,
+                );
+                let points = loader::load_points(&path)?;
+                (
+                    path.display().to_string(),
+                    Some(path),
+                    LayerData::Points(points),
+                )
+            }
+        };
+        Ok(MapLayer {
+            name,
+            path,
+            data,
+            color,
+            visible: self.visible,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_layers() {
+        let manifest: Manifest = toml::from_str(
+            r##"
+            [[layer]]
+            type = "world"
+            color = "#336699"
+
+            [[layer]]
+            type = "wkt"
+            path = "roads.wkt"
+            visible = false
+            "##,
+        )
+        .unwrap();
+        assert_eq!(manifest.layers.len(), 2);
+        assert_eq!(manifest.layers[0].kind, LayerKind::World);
+        assert!(manifest.layers[0].visible);
+        assert_eq!(manifest.layers[1].path, Some(PathBuf::from("roads.wkt")));
+        assert!(!manifest.layers[1].visible);
+
+        let layers = Manifest {
+            layers: manifest.layers.into_iter().take(1).collect(),
+            view: None,
+        }
+        .into_layers(Path::new("."))
+        .unwrap();
+        assert_eq!(layers[0].color, Color::Rgb(0x33, 0x66, 0x99));
+    }
+
+    #[test]
+    fn export_round_trip() {
+        let layers = [MapLayer {
+            visible: false,
+            ..MapLayer::world(Color::Rgb(1, 2, 3))
+        }];
+        let manifest = Manifest::new(&layers, [-10., -5., 10., 5.], Projection::Mercator);
+        let toml = toml::to_string(&manifest).unwrap();
+        assert!(toml.contains("projection = \"mercator\""), "{toml}");
+        let manifest: Manifest = toml::from_str(&toml).unwrap();
+        assert_eq!(
+            manifest.view,
+            Some(ViewSpec {
+                bounds: [-10., -5., 10., 5.],
+                projection: Some(Projection::Mercator),
+            })
+        );
+        assert_eq!(manifest.into_layers(Path::new(".")).unwrap(), layers);
+    }
+
+    #[test]
+    fn export_line_formats() {
+        let layer = |format, path: &str| MapLayer {
+            path: Some(path.into()),
+            data: LayerData::Lines(Vec::new(), format),
+            ..MapLayer::world(Color::Yellow)
+        };
+        // the format the lines were read in counts, not the file extension
+        let layers = [
+            layer(LineFormat::GeoJson, "roads.json"),
+            layer(LineFormat::Wkt, "rivers.geojson"),
+        ];
+        let manifest = Manifest::new(&layers, [-10., -5., 10., 5.], Projection::default());
+        let kinds: Vec<_> = manifest.layers.iter().map(|layer| layer.kind).collect();
+        assert_eq!(kinds, [LayerKind::GeoJson, LayerKind::Wkt]);
+    }
+
+    #[test]
+    fn reject_invalid_layers() {
+        assert!(toml::from_str::<Manifest>("[[layer]]\ntype = \"shapefile\"").is_err());
+        let manifest: Manifest = toml::from_str("[[layer]]\ntype = \"wkt\"").unwrap();
+        assert!(manifest.into_layers(Path::new(".")).is_err());
+        let manifest: Manifest =
+            toml::from_str("[[layer]]\ntype = \"world\"\ncolor = \"plaid\"").unwrap();
+        assert!(manifest.into_layers(Path::new(".")).is_err());
+
+        for (layer, reason) in [
+            (
+                "type = \"csv\"\npath = \"cities.csv\"",
+                "csv layers are not supported",
+            ),
+            (
+                "type = \"gpx\"\npath = \"ride.gpx\"",
+                "gpx layers are not supported",
+            ),
+            (
+                "type = \"world\"\nglyph = \"*\"",
+                "glyph `*` is not supported",
+            ),
+        ] {
+            let manifest: Manifest = toml::from_str(&format!("[[layer]]\n{layer}")).unwrap();
+            let err = manifest.into_layers(Path::new(".")).unwrap_err();
+            assert!(format!("{err:#}").contains(reason), "{err:#}");
+        }
+    }
+}
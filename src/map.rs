@@ -1,8 +1,66 @@
 include!(concat!(env!("OUT_DIR"), "/coordinates.rs"));
 
 
+use std::{cell::Cell, collections::HashMap, path::PathBuf, sync::OnceLock};
+
 use ratatui::style::Color;
-use ratatui::widgets::canvas::{Painter, Shape};
+use ratatui::widgets::canvas::{Line, Painter, Shape};
+
+use crate::geo::{self, Projection};
+
+/// A sequence of (lon, lat) positions drawn as connected segments
+pub type Polyline = Vec<(f64, f64)>;
+
+/// What a layer of the map draws
+#[derive(Debug, Clone, PartialEq)]
+pub enum LayerData {
+    /// the baked world coastline
+    World,
+    /// lines loaded from a file in the given format
+    Lines(Vec<Polyline>, LineFormat),
+    /// coordinates loaded from a file, drawn like the baked coastline
+    Points(Points),
+}
+
+/// File format lines were read from, to write the layer back to a manifest
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum LineFormat {
+    Wkt,
+    GeoJson,
+}
+
+/// Coordinates read at runtime rather than baked at build time
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Points {
+    /// (lon, lat) positions
+    pub coordinates: Vec<(f64, f64)>,
+    /// indices of `coordinates` where a new path starts
+    pub breaks: Vec<usize>,
+}
+
+/// Entry of the layer stack, layers are drawn in order
+#[derive(Debug, Clone, PartialEq)]
+pub struct MapLayer {
+    /// where the layer comes from, to report it to the user
+    pub name: String,
+    /// file the data was read from
+    pub path: Option<PathBuf>,
+    pub data: LayerData,
+    pub color: Color,
+    pub visible: bool,
+}
+
+impl MapLayer {
+    pub fn world(color: Color) -> Self {
+        Self {
+            name: "world".to_string(),
+            path: None,
+            data: LayerData::World,
+            color,
+            visible: true,
+        }
+    }
+}
 
 #[derive(Debug, Clone, Default, Copy, Eq, PartialEq, Hash)]
 pub enum WorldResolution {
@@ -13,23 +71,760 @@ pub enum WorldResolution {
 }
 
 impl WorldResolution {
-    const fn data(self) -> &'static [(f64, f64)] {
-        &COORDINATES
+    /// Resolution detailed enough for a viewport `span` degrees wide
+    pub fn for_span(span: f64) -> Self {
+        if span > 180. {
+            Self::Low
+        } else if span > 45. {
+            Self::Med
+        } else {
+            Self::High
+        }
+    }
+
+    /// Low, Med, High and back to Low
+    pub fn next(self) -> Self {
+        match self {
+            Self::Low => Self::Med,
+            Self::Med => Self::High,
+            Self::High => Self::Low,
+        }
+    }
+
+    pub fn finer(self) -> Self {
+        match self {
+            Self::Low => Self::Med,
+            Self::Med | Self::High => Self::High,
+        }
+    }
+
+    pub fn coarser(self) -> Self {
+        match self {
+            Self::Low | Self::Med => Self::Low,
+            Self::High => Self::Med,
+        }
+    }
+
+    pub const fn data(self) -> &'static [(f64, f64)] {
+        match self {
+            Self::Low => &COORDINATES_LOW,
+            Self::Med => &COORDINATES_MED,
+            Self::High => &COORDINATES_HIGH,
+        }
+    }
+
+    /// `data` bucketed by longitude, built on first use
+    pub fn columns(self) -> &'static [Vec<usize>] {
+        static COLUMNS: [OnceLock<Vec<Vec<usize>>>; 3] = [const { OnceLock::new() }; 3];
+        COLUMNS[self as usize].get_or_init(|| longitude_columns(self.data()))
+    }
+
+    /// Indices of `data` where a new coastline path starts
+    pub const fn path_breaks(self) -> &'static [usize] {
+        match self {
+            Self::Low => &PATH_BREAKS_LOW,
+            Self::Med => &PATH_BREAKS_MED,
+            Self::High => &PATH_BREAKS_HIGH,
+        }
     }
 }
 
-#[derive(Debug, Default, Clone, Eq, PartialEq, Hash)]
-pub struct WorldMap {
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct WorldMap<'a> {
     pub resolution: WorldResolution,
     pub color: Color,
+    /// (lon, lat) coordinates borrowed from the caller, drawn instead of the baked
+    /// dataset of `resolution`
+    pub data: Option<&'a [(f64, f64)]>,
+    /// Most grid points lit in a single terminal cell, points that would light
+    /// another one in an already dense cell are skipped. `None` paints every point
+    pub max_points_per_cell: Option<u8>,
+    /// adds how many points were painted, so layers sharing it sum up
+    pub drawn: Option<&'a Cell<usize>>,
+    /// 1 paints each point alone, larger values also paint the neighbouring grid
+    /// points up to `thickness - 1` away, making the coastline bolder
+    pub thickness: u8,
+    /// size of one canvas grid point in map units, to reach the neighbours when
+    /// `thickness` is above 1
+    pub grid_step: (f64, f64),
+    /// also paint every point 360 degrees east and west, for views crossing the
+    /// antimeridian
+    pub wrap: bool,
+    /// join consecutive points of a path with lines instead of painting them
+    /// alone, `max_points_per_cell` and `thickness` don't apply then
+    pub line_mode: bool,
+    /// indices of `data` where a new path starts, the baked datasets bring their own
+    pub path_breaks: &'a [usize],
+    /// layout of the coordinates, the canvas bounds have to be projected the same way
+    pub projection: Projection,
+    /// center of the view, which the orthographic projection looks down on
+    pub center: (f64, f64),
+    /// `[min_lon, min_lat, max_lon, max_lat]` of the view, points outside are skipped
+    /// before projecting them. `None` leaves the culling to the painter
+    pub bounds: Option<[f64; 4]>,
+}
+
+impl<'a> WorldMap<'a> {
+    /// Map drawing coordinates the caller already holds in memory, without copying them
+    pub fn from_slice(data: &'a [(f64, f64)], color: Color) -> Self {
+        Self {
+            resolution: WorldResolution::default(),
+            color,
+            data: Some(data),
+            ..Default::default()
+        }
+    }
+}
+
+impl WorldMap<'_> {
+    /// Longitude offsets each point is painted at
+    fn offsets(&self) -> &'static [f64] {
+        wrap_offsets(self.wrap)
+    }
+
+    /// Points of `data` shifted by `offset` that fall within `bounds`, projected.
+    /// The baked datasets are looked up by longitude instead of scanned whole
+    fn visible<'d>(
+        &'d self,
+        data: &'d [(f64, f64)],
+        offset: f64,
+    ) -> impl Iterator<Item = (f64, f64)> + 'd {
+        let points: Box<dyn Iterator<Item = (f64, f64)>> = match (self.data, self.bounds) {
+            (None, Some([min_x, _, max_x, _])) => {
+                let columns = self.resolution.columns();
+                let range = column_range(min_x - offset, max_x - offset);
+                Box::new(
+                    columns[range]
+                        .iter()
+                        .flatten()
+                        .map(move |&index| data[index]),
+                )
+            }
+            _ => Box::new(data.iter().copied()),
+        };
+        points.filter_map(move |(x, y)| {
+            let x = x + offset;
+            if !self.in_bounds(x, y) {
+                return None;
+            }
+            self.projection.point(x, y, self.center)
+        })
+    }
+
+    fn in_bounds(&self, x: f64, y: f64) -> bool {
+        self.bounds.is_none_or(|[min_x, min_y, max_x, max_y]| {
+            (min_x..=max_x).contains(&x) && (min_y..=max_y).contains(&y)
+        })
+    }
+
+    /// Draws the segments between consecutive points of each path, returns how many
+    fn draw_lines(
+        &self,
+        data: &[(f64, f64)],
+        breaks: &[usize],
+        offset: f64,
+        painter: &mut Painter,
+    ) -> usize {
+        let mut drawn = 0;
+        for (index, segment) in data.windows(2).enumerate() {
+            let ((x1, y1), (x2, y2)) = (segment[0], segment[1]);
+            // a jump across the antimeridian would streak over the whole map
+            if breaks.binary_search(&(index + 1)).is_ok() || (x2 - x1).abs() > 180. {
+                continue;
+            }
+            let (x1, x2) = (x1 + offset, x2 + offset);
+            // a segment is only drawn when both ends are on the canvas, so those
+            // leaving the view are cut at its edge rather than dropped
+            let ((x1, y1), (x2, y2)) = match self.bounds {
+                Some(bounds) => match geo::clip_segment((x1, y1), (x2, y2), bounds) {
+                    Some(segment) => segment,
+                    None => continue,
+                },
+                None => ((x1, y1), (x2, y2)),
+            };
+            let (Some((x1, y1)), Some((x2, y2))) = (
+                self.projection.point(x1, y1, self.center),
+                self.projection.point(x2, y2, self.center),
+            ) else {
+                continue;
+            };
+            if painter.get_point(x1, y1).is_some() && painter.get_point(x2, y2).is_some() {
+                Line::new(x1, y1, x2, y2, self.color).draw(painter);
+                drawn += 1;
+            }
+        }
+        drawn
+    }
+}
+
+impl Shape for WorldMap<'_> {
+    fn draw(&self, painter: &mut Painter) {
+        let (data, breaks) = match self.data {
+            Some(data) => (data, self.path_breaks),
+            None => (self.resolution.data(), self.resolution.path_breaks()),
+        };
+        // grid points lit per terminal cell as a bit each, a braille cell holds 2x4
+        let mut density: HashMap<(usize, usize), u8> = HashMap::new();
+        let mut drawn = 0;
+        let radius = i32::from(self.thickness.max(1)) - 1;
+        let (step_x, step_y) = self.grid_step;
+        for &offset in self.offsets() {
+            if self.line_mode {
+                drawn += self.draw_lines(data, breaks, offset, painter);
+                continue;
+            }
+            for (x, y) in self.visible(data, offset) {
+                if painter.get_point(x, y).is_none() {
+                    continue;
+                }
+                // the point itself, and its neighbours when the coastline is bolder
+                for dx in -radius..=radius {
+                    for dy in -radius..=radius {
+                        let (x, y) = (x + f64::from(dx) * step_x, y + f64::from(dy) * step_y);
+                        // neighbours outside the canvas are dropped rather than wrapped
+                        let Some((x, y)) = painter.get_point(x, y) else {
+                            continue;
+                        };
+                        if let Some(max) = self.max_points_per_cell {
+                            let lit = density.entry((x / 2, y / 4)).or_default();
+                            let dot = 1 << (y % 4 * 2 + x % 2);
+                            // a point on a dot already lit costs nothing
+                            if *lit & dot == 0 {
+                                if lit.count_ones() >= u32::from(max) {
+                                    continue;
+                                }
+                                *lit |= dot;
+                            }
+                        }
+                        painter.paint(x, y, self.color);
+                        drawn += 1;
+                    }
+                }
+            }
+        }
+        if let Some(counter) = self.drawn {
+            counter.set(counter.get() + drawn);
+        }
+    }
+}
+
+/// Indices of `data` in one bucket per degree of longitude from -180, keeping their
+/// order within each bucket
+fn longitude_columns(data: &[(f64, f64)]) -> Vec<Vec<usize>> {
+    let mut columns = vec![Vec::new(); 360];
+    for (index, (lon, _)) in data.iter().enumerate() {
+        columns[column(*lon)].push(index);
+    }
+    columns
+}
+
+fn column(lon: f64) -> usize {
+    (lon + 180.).floor().clamp(0., 359.) as usize
+}
+
+/// Buckets of `longitude_columns` holding the longitudes between `min` and `max`
+fn column_range(min: f64, max: f64) -> std::ops::Range<usize> {
+    if max < -180. || min > 180. {
+        return 0..0;
+    }
+    column(min)..column(max) + 1
+}
+
+/// Index and position of the point of `data` nearest to `target`, measuring in
+/// cells of `cell_size` map units and ignoring points more than `max_cells` away.
+/// With the `columns` of a baked dataset only the buckets within reach are searched
+pub fn nearest_point(
+    data: &[(f64, f64)],
+    columns: Option<&[Vec<usize>]>,
+    target: (f64, f64),
+    cell_size: (f64, f64),
+    max_cells: f64,
+) -> Option<(usize, (f64, f64))> {
+    let (x, y) = target;
+    let (width, height) = cell_size;
+    let indices: Box<dyn Iterator<Item = usize>> = match columns {
+        Some(columns) => {
+            let range = column_range(x - max_cells * width, x + max_cells * width);
+            Box::new(columns[range].iter().flatten().copied())
+        }
+        None => Box::new(0..data.len()),
+    };
+    indices
+        .map(|index| (index, data[index]))
+        // cheap box test before measuring the distance
+        .filter(|(_, (px, py))| {
+            (px - x).abs() <= max_cells * width && (py - y).abs() <= max_cells * height
+        })
+        .map(|(index, (px, py))| (index, (px, py), ((px - x) / width).hypot((py - y) / height)))
+        .filter(|(_, _, distance)| *distance <= max_cells)
+        .min_by(|a, b| a.2.total_cmp(&b.2))
+        .map(|(index, point, _)| (index, point))
+}
+
+/// Douglas–Peucker simplification, dropping the points that are closer than
+/// `tolerance` to the simplified line
+pub fn simplify(points: &[(f64, f64)], tolerance: f64) -> Vec<(f64, f64)> {
+    if points.len() < 3 {
+        return points.to_vec();
+    }
+    let mut keep = vec![false; points.len()];
+    keep[0] = true;
+    keep[points.len() - 1] = true;
+    // a stack rather than recursion, the coastline has too many points for it
+    let mut ranges = vec![(0, points.len() - 1)];
+    while let Some((start, end)) = ranges.pop() {
+        let farthest = (start + 1..end)
+            .map(|i| (i, segment_distance(points[i], points[start], points[end])))
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+        if let Some((index, _)) = farthest.filter(|(_, distance)| *distance > tolerance) {
+            keep[index] = true;
+            ranges.push((start, index));
+            ranges.push((index, end));
+        }
+    }
+    points
+        .iter()
+        .zip(keep)
+        .filter_map(|(point, keep)| keep.then_some(*point))
+        .collect()
+}
+
+/// `simplify` applied to each path of `points` on its own, so separate paths are
+/// never merged. Returns the simplified points and where their paths start
+pub fn simplify_paths(
+    points: &[(f64, f64)],
+    breaks: &[usize],
+    tolerance: f64,
+) -> (Vec<(f64, f64)>, Vec<usize>) {
+    let mut simplified = Vec::new();
+    let mut simplified_breaks = Vec::new();
+    let starts = std::iter::once(0).chain(breaks.iter().copied());
+    let ends = breaks.iter().copied().chain(std::iter::once(points.len()));
+    for (start, end) in starts.zip(ends) {
+        if !simplified.is_empty() {
+            simplified_breaks.push(simplified.len());
+        }
+        simplified.extend(simplify(&points[start..end], tolerance));
+    }
+    (simplified, simplified_breaks)
+}
+
+/// Distance from `point` to the segment between `a` and `b`
+fn segment_distance(point: (f64, f64), a: (f64, f64), b: (f64, f64)) -> f64 {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let length = dx * dx + dy * dy;
+    let t = if length == 0. {
+        0.
+    } else {
+        (((point.0 - a.0) * dx + (point.1 - a.1) * dy) / length).clamp(0., 1.)
+    };
+    (point.0 - (a.0 + t * dx)).hypot(point.1 - (a.1 + t * dy))
+}
+
+/// Longitude offsets to draw at so that a view crossing the antimeridian also shows
+/// what lies a turn of the world east and west
+pub fn wrap_offsets(wrap: bool) -> &'static [f64] {
+    if wrap {
+        &[0., -360., 360.]
+    } else {
+        &[0.]
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Polylines<'a> {
+    pub lines: &'a [Polyline],
+    pub color: Color,
+    pub projection: Projection,
+    /// also draw every line 360 degrees east and west
+    pub wrap: bool,
+    /// center of the view, which the orthographic projection looks down on
+    pub center: (f64, f64),
 }
 
-impl Shape for WorldMap {
+impl Shape for Polylines<'_> {
     fn draw(&self, painter: &mut Painter) {
-        for (x, y) in self.resolution.data() {
-            if let Some((x, y)) = painter.get_point(*x, *y) {
+        for &offset in wrap_offsets(self.wrap) {
+            for line in self.lines {
+                self.draw_line(line, offset, painter);
+            }
+        }
+    }
+}
+
+impl Polylines<'_> {
+    fn draw_line(&self, line: &[(f64, f64)], offset: f64, painter: &mut Painter) {
+        // a lone position, such as a GeoJSON point
+        let point = |(x, y): (f64, f64)| self.projection.point(x + offset, y, self.center);
+        if let [position] = line[..] {
+            if let Some((x, y)) = point(position).and_then(|(x, y)| painter.get_point(x, y)) {
                 painter.paint(x, y, self.color);
             }
+            return;
+        }
+        for segment in line.windows(2) {
+            // segments reaching past the latitude limit of the projection, or to the
+            // far side of the globe, are dropped
+            if let (Some((x1, y1)), Some((x2, y2))) = (point(segment[0]), point(segment[1])) {
+                Line::new(x1, y1, x2, y2, self.color).draw(painter);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::{buffer::Buffer, layout::Rect, widgets::canvas::Canvas, widgets::Widget};
+
+    #[test]
+    fn render_borrowed_data() {
+        let points = vec![(0.0, 0.0)];
+        let mut buf = Buffer::empty(Rect::new(0, 0, 5, 5));
+
+        Canvas::default()
+            .x_bounds([-1., 1.])
+            .y_bounds([-1., 1.])
+            .paint(|ctx| ctx.draw(&WorldMap::from_slice(&points, Color::Red)))
+            .render(buf.area, &mut buf);
+
+        let painted: Vec<_> = buf
+            .content()
+            .iter()
+            .filter(|cell| cell.symbol() != " ")
+            .collect();
+        assert_eq!(painted.len(), 1);
+        assert_eq!(painted[0].fg, Color::Red);
+        assert_eq!(buf.content()[12].fg, Color::Red);
+    }
+
+    #[test]
+    fn baked_data_is_lon_lat() {
+        for resolution in [
+            WorldResolution::Low,
+            WorldResolution::Med,
+            WorldResolution::High,
+        ] {
+            assert!(resolution.data().iter().all(|(_, lat)| lat.abs() <= 90.));
         }
+
+        // Greenwich, on a canvas of one cell per 10 degrees
+        let greenwich = [(0.0, 51.48)];
+        let mut buf = Buffer::empty(Rect::new(0, 0, 36, 18));
+        Canvas::default()
+            .x_bounds([-180., 180.])
+            .y_bounds([-90., 90.])
+            .paint(|ctx| ctx.draw(&WorldMap::from_slice(&greenwich, Color::Red)))
+            .render(buf.area, &mut buf);
+
+        let painted: Vec<_> = buf
+            .content()
+            .iter()
+            .enumerate()
+            .filter(|(_, cell)| cell.symbol() != " ")
+            .map(|(index, _)| buf.pos_of(index))
+            .collect();
+        // the meridian falls on the boundary between the two middle columns
+        assert_eq!(painted.len(), 1);
+        assert!(matches!(painted[0], (17 | 18, 3)), "{painted:?}");
+    }
+
+    #[test]
+    fn wrap_across_antimeridian() {
+        let points = [(-175., 0.)];
+        let drawn = Cell::new(0);
+        let mut buf = Buffer::empty(Rect::new(0, 0, 10, 5));
+
+        Canvas::default()
+            .x_bounds([170., 190.])
+            .y_bounds([-5., 5.])
+            .paint(|ctx| {
+                ctx.draw(&WorldMap {
+                    wrap: true,
+                    drawn: Some(&drawn),
+                    ..WorldMap::from_slice(&points, Color::Red)
+                })
+            })
+            .render(buf.area, &mut buf);
+
+        assert_eq!(drawn.get(), 1);
+    }
+
+    #[test]
+    fn layers_add_up_drawn() {
+        let (first, second) = ([(-3., 0.)], [(2., 1.), (3., -1.)]);
+        let drawn = Cell::new(0);
+        let mut buf = Buffer::empty(Rect::new(0, 0, 10, 5));
+
+        Canvas::default()
+            .x_bounds([-5., 5.])
+            .y_bounds([-5., 5.])
+            .paint(|ctx| {
+                for data in [&first[..], &second[..]] {
+                    ctx.draw(&WorldMap {
+                        drawn: Some(&drawn),
+                        ..WorldMap::from_slice(data, Color::Red)
+                    })
+                }
+            })
+            .render(buf.area, &mut buf);
+
+        assert_eq!(drawn.get(), 3);
+    }
+
+    #[test]
+    fn wrap_polylines() {
+        let lines = [vec![(-175., -4.), (-175., 4.)], vec![(-172., 0.)]];
+        let mut buf = Buffer::empty(Rect::new(0, 0, 10, 5));
+
+        Canvas::default()
+            .x_bounds([170., 190.])
+            .y_bounds([-5., 5.])
+            .paint(|ctx| {
+                ctx.draw(&Polylines {
+                    lines: &lines,
+                    color: Color::Red,
+                    projection: Projection::Equirectangular,
+                    wrap: true,
+                    center: (180., 0.),
+                })
+            })
+            .render(buf.area, &mut buf);
+
+        // -175 and -172 are 185 and 188 a turn east
+        assert_ne!(buf[(7, 2)].symbol(), " ");
+        assert_ne!(buf[(8, 2)].symbol(), " ");
+    }
+
+    #[test]
+    fn nearest_point_within_reach() {
+        let points = [(0., 0.), (5., 5.), (1., 1.5)];
+        assert_eq!(
+            nearest_point(&points, None, (1., 1.), (1., 1.), 2.),
+            Some((2, (1., 1.5)))
+        );
+        assert_eq!(nearest_point(&points, None, (10., 0.), (1., 1.), 2.), None);
+    }
+
+    #[test]
+    fn nearest_point_in_nearby_columns() {
+        let points = [(0.5, 0.), (-120.5, 12.), (-120.2, 10.5), (60., 10.)];
+        let columns = longitude_columns(&points);
+        assert_eq!(
+            nearest_point(&points, Some(&columns), (-120., 10.), (1., 1.), 2.),
+            Some((2, (-120.2, 10.5)))
+        );
+        assert_eq!(
+            nearest_point(&points, Some(&columns), (30., 10.), (1., 1.), 2.),
+            None
+        );
+        // the buckets agree with the full scan
+        for target in [(0., 0.), (-121., 11.), (59.5, 9.)] {
+            assert_eq!(
+                nearest_point(&points, Some(&columns), target, (1., 1.), 2.),
+                nearest_point(&points, None, target, (1., 1.), 2.)
+            );
+        }
+    }
+
+    #[test]
+    fn simplify_keeps_corners() {
+        let line = [(0., 0.), (1., 0.01), (2., 0.), (3., 2.), (4., 0.)];
+        assert_eq!(
+            simplify(&line, 0.1),
+            vec![(0., 0.), (2., 0.), (3., 2.), (4., 0.)]
+        );
+        assert_eq!(simplify(&line, 5.), vec![(0., 0.), (4., 0.)]);
+    }
+
+    #[test]
+    fn simplify_each_path() {
+        let points = [
+            (0., 0.),
+            (1., 0.),
+            (2., 0.),
+            (10., 10.),
+            (11., 10.),
+            (12., 10.),
+        ];
+        let (simplified, breaks) = simplify_paths(&points, &[3], 0.1);
+        assert_eq!(simplified, vec![(0., 0.), (2., 0.), (10., 10.), (12., 10.)]);
+        assert_eq!(breaks, vec![2]);
+    }
+
+    #[test]
+    fn line_mode_breaks_paths() {
+        // two paths side by side, joining them would cross the middle row
+        let points = [(-1., -1.), (-1., 1.), (1., 1.), (1., -1.)];
+        let drawn = Cell::new(0);
+        let mut buf = Buffer::empty(Rect::new(0, 0, 10, 5));
+
+        Canvas::default()
+            .x_bounds([-1., 1.])
+            .y_bounds([-1., 1.])
+            .paint(|ctx| {
+                ctx.draw(&WorldMap {
+                    line_mode: true,
+                    path_breaks: &[2],
+                    drawn: Some(&drawn),
+                    ..WorldMap::from_slice(&points, Color::Red)
+                })
+            })
+            .render(buf.area, &mut buf);
+
+        assert_eq!(drawn.get(), 2);
+        // the segment between the paths would have filled the top row
+        assert_eq!(buf[(5, 0)].symbol(), " ");
+        assert_ne!(buf[(0, 2)].symbol(), " ");
+    }
+
+    #[test]
+    fn cull_outside_bounds() {
+        // three points inside the view, one beyond each edge of it and one far away
+        let data = [
+            (0., 45.),
+            (-5., 42.),
+            (9.5, 49.),
+            (20., 45.),
+            (0., 60.),
+            (-15., 45.),
+            (0., 30.),
+            (-170., -30.),
+        ];
+        let render = |bounds| {
+            let mut buf = Buffer::empty(Rect::new(0, 0, 20, 10));
+            Canvas::default()
+                .x_bounds([-10., 10.])
+                .y_bounds([40., 50.])
+                .paint(|ctx| {
+                    ctx.draw(&WorldMap {
+                        bounds,
+                        ..WorldMap::from_slice(&data, Color::Red)
+                    })
+                })
+                .render(buf.area, &mut buf);
+            buf
+        };
+        let bounds = [-10., 40., 10., 50.];
+        assert_eq!(render(Some(bounds)), render(None));
+
+        let map = WorldMap {
+            bounds: Some(bounds),
+            ..WorldMap::from_slice(&data, Color::Red)
+        };
+        assert_eq!(map.visible(&data, 0.).count(), 3);
+    }
+
+    #[test]
+    fn cull_baked_data_by_column() {
+        let resolution = WorldResolution::Med;
+        let data = resolution.data();
+        let columns = resolution.columns();
+        assert_eq!(columns.iter().map(Vec::len).sum::<usize>(), data.len());
+        assert!(columns[column(-3.)]
+            .iter()
+            .all(|&i| data[i].0.floor() == -3.));
+
+        // the view east of the antimeridian, reached with the 360 offset
+        let bounds = [170., -60., 200., 60.];
+        let indexed = WorldMap {
+            resolution,
+            bounds: Some(bounds),
+            ..Default::default()
+        };
+        let scanned = WorldMap {
+            bounds: Some(bounds),
+            ..WorldMap::from_slice(data, Color::Red)
+        };
+        for offset in [0., 360.] {
+            let mut expected: Vec<_> = scanned.visible(data, offset).collect();
+            let mut found: Vec<_> = indexed.visible(data, offset).collect();
+            expected.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            found.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            assert_eq!(found, expected);
+        }
+        assert_eq!(column_range(190., 200.), 0..0);
+    }
+
+    #[test]
+    fn line_mode_clips_at_the_edges() {
+        // a single segment crossing the whole view
+        let points = [(-10., 0.), (10., 0.)];
+        let drawn = Cell::new(0);
+        let mut buf = Buffer::empty(Rect::new(0, 0, 10, 5));
+
+        Canvas::default()
+            .x_bounds([-1., 1.])
+            .y_bounds([-1., 1.])
+            .paint(|ctx| {
+                ctx.draw(&WorldMap {
+                    line_mode: true,
+                    bounds: Some([-1., -1., 1., 1.]),
+                    drawn: Some(&drawn),
+                    ..WorldMap::from_slice(&points, Color::Red)
+                })
+            })
+            .render(buf.area, &mut buf);
+
+        assert_eq!(drawn.get(), 1);
+        assert!((0..10).all(|x| buf[(x, 2)].symbol() != " "));
+    }
+
+    #[test]
+    fn thick_coastline() {
+        let points = [(0., 0.)];
+        let drawn = Cell::new(0);
+        let mut buf = Buffer::empty(Rect::new(0, 0, 5, 5));
+
+        Canvas::default()
+            .x_bounds([-1., 1.])
+            .y_bounds([-1., 1.])
+            .paint(|ctx| {
+                ctx.draw(&WorldMap {
+                    thickness: 2,
+                    // the canvas is 10x20 grid points
+                    grid_step: (2. / 9., 2. / 19.),
+                    drawn: Some(&drawn),
+                    ..WorldMap::from_slice(&points, Color::Red)
+                })
+            })
+            .render(buf.area, &mut buf);
+
+        // the point and its 8 neighbours
+        assert_eq!(drawn.get(), 9);
+        let dots: u32 = buf
+            .content()
+            .iter()
+            .filter_map(|cell| cell.symbol().chars().next())
+            .map(|c| (c as u32).saturating_sub(0x2800).count_ones())
+            .sum();
+        assert_eq!(dots, 9);
+    }
+
+    #[test]
+    fn decimate_dense_cells() {
+        // each of the 8 dots of a single cell, then the first one again
+        let points: Vec<_> = (0..8)
+            .chain([0])
+            .map(|i| (f64::from(i % 2), f64::from(i / 2)))
+            .collect();
+        let drawn = Cell::new(0);
+        let mut buf = Buffer::empty(Rect::new(0, 0, 1, 1));
+
+        Canvas::default()
+            .x_bounds([0., 1.])
+            .y_bounds([0., 3.])
+            .paint(|ctx| {
+                ctx.draw(&WorldMap {
+                    max_points_per_cell: Some(3),
+                    drawn: Some(&drawn),
+                    ..WorldMap::from_slice(&points, Color::Red)
+                })
+            })
+            .render(buf.area, &mut buf);
+
+        assert_eq!(drawn.get(), 4);
+        let dots = buf[(0, 0)].symbol().chars().next().unwrap() as u32 - 0x2800;
+        assert_eq!(dots.count_ones(), 3);
     }
 }
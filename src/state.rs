@@ -0,0 +1,61 @@
+use std::{
+    env,
+    path::{Path, PathBuf},
+};
+
+use color_eyre::Result;
+
+use crate::manifest::ViewSpec;
+
+/// File keeping the last view between runs, under `$XDG_STATE_HOME` or
+/// `~/.local/state`
+pub fn view_path() -> Option<PathBuf> {
+    let dir = env::var_os("XDG_STATE_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".local/state")))?;
+    Some(dir.join("plou").join("view.toml"))
+}
+
+/// Bounds of the view saved at `path`, `None` if there is none or it can't be used
+pub fn load_view(path: &Path) -> Option<[f64; 4]> {
+    let view: ViewSpec = toml::from_str(&std::fs::read_to_string(path).ok()?).ok()?;
+    let [min_x, min_y, max_x, max_y] = view.bounds;
+    let usable = view.bounds.iter().all(|value| value.is_finite())
+        && min_x < max_x
+        && min_y < max_y
+        && min_y >= -90.
+        && max_y <= 90.;
+    usable.then_some(view.bounds)
+}
+
+pub fn save_view(path: &Path, bounds: [f64; 4]) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let view = ViewSpec {
+        bounds,
+        projection: None,
+    };
+    std::fs::write(path, toml::to_string(&view)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn view_round_trip() {
+        let dir = env::temp_dir().join("plou-state");
+        let path = dir.join("view.toml");
+        save_view(&path, [-10., -5., 10.5, 5.]).unwrap();
+        assert_eq!(load_view(&path), Some([-10., -5., 10.5, 5.]));
+
+        std::fs::write(&path, "bounds = [10, 0, -10, 5]").unwrap();
+        assert_eq!(load_view(&path), None);
+        std::fs::write(&path, "not toml").unwrap();
+        assert_eq!(load_view(&path), None);
+        assert_eq!(load_view(&dir.join("missing.toml")), None);
+    }
+}
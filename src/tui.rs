@@ -1,11 +1,17 @@
-use std::io::{self, stdout, Stdout};
+use std::io::{self, stdout, Stdout, Write};
 
-use crossterm::event::{DisableMouseCapture, EnableMouseCapture};
+use crossterm::event::{
+    DisableFocusChange, DisableMouseCapture, EnableFocusChange, EnableMouseCapture,
+    KeyboardEnhancementFlags, PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+};
 use ratatui::{
     backend::CrosstermBackend,
     crossterm::{
         execute,
-        terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+        terminal::{
+            disable_raw_mode, enable_raw_mode, supports_keyboard_enhancement, EnterAlternateScreen,
+            LeaveAlternateScreen,
+        },
     },
     Terminal,
 };
@@ -16,10 +22,24 @@ pub fn init() -> io::Result<Tui> {
     execute!(stdout(), EnterAlternateScreen)?;
     enable_raw_mode()?;
     execute!(stdout(), EnableMouseCapture)?;
+    // to tell when the mouse leaves the window
+    execute!(stdout(), EnableFocusChange)?;
     set_panic_hook();
     Terminal::new(CrosstermBackend::new(stdout()))
 }
 
+/// Asks the terminal to report key repeats and releases, returns whether it can
+pub fn enable_key_release_events() -> io::Result<bool> {
+    if !supports_keyboard_enhancement()? {
+        return Ok(false);
+    }
+    execute!(
+        stdout(),
+        PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::REPORT_EVENT_TYPES)
+    )?;
+    Ok(true)
+}
+
 fn set_panic_hook() {
     let hook = std::panic::take_hook();
     std::panic::set_hook(Box::new(move |panic_info| {
@@ -29,9 +49,54 @@ fn set_panic_hook() {
 }
 
 pub fn restore() -> io::Result<()> {
+    // ignored by terminals where the flags were never pushed
+    execute!(stdout(), PopKeyboardEnhancementFlags)?;
     execute!(stdout(), LeaveAlternateScreen)?;
     execute!(stdout(), DisableMouseCapture)?;
+    execute!(stdout(), DisableFocusChange)?;
     disable_raw_mode()?;
 
     Ok(())
 }
+
+/// Copies `text` with the OSC 52 escape sequence, which works over ssh too.
+/// Terminals that don't support it ignore the sequence
+pub fn copy_to_clipboard(text: &str) -> io::Result<()> {
+    let mut stdout = stdout();
+    write!(stdout, "\x1b]52;c;{}\x07", base64(text.as_bytes()))?;
+    stdout.flush()
+}
+
+/// Standard base64 with padding, as OSC 52 expects
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let group = chunk.iter().enumerate().fold(0u32, |group, (i, &byte)| {
+            group | u32::from(byte) << (16 - 8 * i)
+        });
+        // a chunk of n bytes fills n + 1 characters, the rest is padding
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(group >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base64_pads() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64(b"-10,20"), "LTEwLDIw");
+    }
+}
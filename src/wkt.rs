@@ -0,0 +1,211 @@
+use crate::map::Polyline;
+
+/// Geometries read from a WKT document
+#[derive(Debug, Default)]
+pub struct Wkt {
+    /// lines and polygon rings, as sequences of (lon, lat)
+    pub polylines: Vec<Polyline>,
+    /// tags of the geometries that were skipped because they are not supported
+    pub skipped: Vec<String>,
+}
+
+/// Malformed WKT
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    /// byte offset in the input where the problem was found
+    pub pos: usize,
+    pub reason: String,
+}
+
+type Result<T> = std::result::Result<T, ParseError>;
+
+/// Parses every geometry in `input`. `LINESTRING`, `MULTILINESTRING`, `POLYGON` and
+/// `MULTIPOLYGON` are turned into polylines; any other geometry type is skipped.
+pub fn parse(input: &str) -> Result<Wkt> {
+    let mut parser = Parser { input, pos: 0 };
+    let mut wkt = Wkt::default();
+    while !parser.at_end() {
+        let tag = parser.tag()?;
+        // SRID=4326;LINESTRING(...) as written by PostGIS
+        if tag == "SRID" {
+            parser.skip_past(';')?;
+            continue;
+        }
+        // dimension modifiers such as `LINESTRING Z (...)`, or `EMPTY`
+        let mut empty = false;
+        while parser.peek().is_some_and(|c| c.is_ascii_alphabetic()) {
+            if parser.tag()? == "EMPTY" {
+                empty = true;
+                break;
+            }
+        }
+        if empty {
+            continue;
+        }
+        match tag.as_str() {
+            "LINESTRING" => wkt.polylines.push(parser.sequence()?),
+            "MULTILINESTRING" | "POLYGON" => wkt.polylines.extend(parser.list(Parser::sequence)?),
+            "MULTIPOLYGON" => {
+                let polygons = parser.list(|p| p.list(Parser::sequence))?;
+                wkt.polylines.extend(polygons.into_iter().flatten());
+            }
+            _ => {
+                parser.skip_group()?;
+                wkt.skipped.push(tag);
+            }
+        }
+    }
+    Ok(wkt)
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn error<T>(&self, reason: String) -> Result<T> {
+        Err(ParseError {
+            pos: self.pos,
+            reason,
+        })
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_whitespace();
+        self.input[self.pos..].chars().next()
+    }
+
+    fn at_end(&mut self) -> bool {
+        self.peek().is_none()
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = &self.input[self.pos..];
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn expect(&mut self, expected: char) -> Result<()> {
+        match self.peek() {
+            Some(c) if c == expected => {
+                self.pos += c.len_utf8();
+                Ok(())
+            }
+            Some(c) => self.error(format!("expected `{expected}` but found `{c}`")),
+            None => self.error(format!(
+                "expected `{expected}` but reached the end of the input"
+            )),
+        }
+    }
+
+    /// Reads a geometry tag such as `POLYGON`, upper cased
+    fn tag(&mut self) -> Result<String> {
+        self.skip_whitespace();
+        let rest = &self.input[self.pos..];
+        let len = rest
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(rest.len());
+        if len == 0 {
+            return self.error("expected a geometry type".to_string());
+        }
+        self.pos += len;
+        Ok(rest[..len].to_ascii_uppercase())
+    }
+
+    fn skip_past(&mut self, delimiter: char) -> Result<()> {
+        match self.input[self.pos..].find(delimiter) {
+            Some(offset) => {
+                self.pos += offset + delimiter.len_utf8();
+                Ok(())
+            }
+            None => self.error(format!("expected `{delimiter}`")),
+        }
+    }
+
+    /// Skips a balanced parenthesized group
+    fn skip_group(&mut self) -> Result<()> {
+        self.expect('(')?;
+        let mut depth = 1;
+        for (offset, c) in self.input[self.pos..].char_indices() {
+            match c {
+                '(' => depth += 1,
+                ')' => depth -= 1,
+                _ => {}
+            }
+            if depth == 0 {
+                self.pos += offset + 1;
+                return Ok(());
+            }
+        }
+        self.error("unbalanced parentheses".to_string())
+    }
+
+    /// `(item, item, ...)`
+    fn list<T>(&mut self, mut item: impl FnMut(&mut Self) -> Result<T>) -> Result<Vec<T>> {
+        self.expect('(')?;
+        let mut items = vec![item(self)?];
+        while self.peek() == Some(',') {
+            self.expect(',')?;
+            items.push(item(self)?);
+        }
+        self.expect(')')?;
+        Ok(items)
+    }
+
+    /// `(x y, x y, ...)`
+    fn sequence(&mut self) -> Result<Polyline> {
+        self.list(Parser::position)
+    }
+
+    /// `x y`, ignoring any z or m values
+    fn position(&mut self) -> Result<(f64, f64)> {
+        self.skip_whitespace();
+        let rest = &self.input[self.pos..];
+        let len = rest.find([',', ')']).unwrap_or(rest.len());
+        let mut values = rest[..len].split_whitespace();
+        let (Some(x), Some(y)) = (values.next(), values.next()) else {
+            return self.error("expected a position".to_string());
+        };
+        match (x.parse::<f64>(), y.parse::<f64>()) {
+            (Ok(x), Ok(y)) => {
+                self.pos += len;
+                Ok((x, y))
+            }
+            _ => self.error(format!("invalid position `{x} {y}`")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_supported_geometries() {
+        let wkt = parse(
+            "LINESTRING (0 0, 10 10)\n\
+             POLYGON ((0 0, 1 0, 1 1, 0 0), (0.2 0.2, 0.4 0.2, 0.2 0.4, 0.2 0.2))\n\
+             MULTILINESTRING ((1 1, 2 2), (3 3, 4 4, 5 5))",
+        )
+        .unwrap();
+        assert_eq!(wkt.polylines.len(), 5);
+        assert_eq!(wkt.polylines[0], vec![(0., 0.), (10., 10.)]);
+        assert_eq!(wkt.polylines[4].len(), 3);
+        assert!(wkt.skipped.is_empty());
+    }
+
+    #[test]
+    fn skips_unsupported_geometries() {
+        let wkt =
+            parse("POINT (1 2) SRID=4326;LINESTRING Z (0 0 5, 1 1 5) LINESTRING EMPTY").unwrap();
+        assert_eq!(wkt.polylines, vec![vec![(0., 0.), (1., 1.)]]);
+        assert_eq!(wkt.skipped, vec!["POINT"]);
+    }
+
+    #[test]
+    fn rejects_malformed_input() {
+        assert!(parse("LINESTRING (0 0, 1)").is_err());
+        assert!(parse("LINESTRING (0 0, 1 1").is_err());
+        assert!(parse("LINESTRING (a b)").is_err());
+    }
+}